@@ -0,0 +1,76 @@
+//! Throughput of catr's unformatted copy against the original line-by-line
+//! implementation and, when it is installed, the system cat.
+//!
+//!     cargo bench -p catr
+//!     CATR_BENCH_MB=4096 cargo bench -p catr
+
+use common::bench::{installed, megabytes, report, write_input, BenchResult, RUNS};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+const PRG: &str = env!("CARGO_BIN_EXE_catr");
+
+fn main() -> BenchResult<()> {
+    let megabytes = megabytes("CATR_BENCH_MB");
+    let size = megabytes * 1024 * 1024;
+
+    let dir = env::temp_dir();
+    let input = dir.join(format!("catr-bench-{}.in", std::process::id()));
+    let output = dir.join(format!("catr-bench-{}.out", std::process::id()));
+    write_input(&input, size, |number| {
+        format!(
+            "{:>10} The quick brown fox jumps over the lazy dog.\n",
+            number
+        )
+    })?;
+
+    println!("copying {} MiB, best of {} runs", megabytes, RUNS);
+    report("line-by-line > /dev/null", size, || {
+        line_by_line(&input, File::create("/dev/null")?)
+    })?;
+    for (name, program) in [("catr", PRG), ("cat", "cat")] {
+        if !installed(program) {
+            continue;
+        }
+        report(&format!("{} > /dev/null", name), size, || {
+            spawn(program, &input, Stdio::from(File::create("/dev/null")?))
+        })?;
+        report(&format!("{} > file", name), size, || {
+            spawn(program, &input, Stdio::from(File::create(&output)?))
+        })?;
+        report(&format!("{} | pipe", name), size, || {
+            let mut child = Command::new(program)
+                .arg(&input)
+                .stdout(Stdio::piped())
+                .spawn()?;
+            io::copy(child.stdout.as_mut().unwrap(), &mut io::sink())?;
+            child.wait()?;
+            Ok(())
+        })?;
+    }
+
+    fs::remove_file(&input)?;
+    let _ = fs::remove_file(&output);
+    Ok(())
+}
+
+/// The copy loop catr used before it learned to pass bytes through: decode
+/// every line and print it through a line-buffered writer like println!.
+fn line_by_line(path: &Path, out: File) -> BenchResult<()> {
+    let mut out = LineWriter::new(out);
+    for line in BufReader::new(File::open(path)?).lines() {
+        writeln!(out, "{}", line?)?;
+    }
+    Ok(())
+}
+
+fn spawn(program: &str, input: &Path, stdout: Stdio) -> BenchResult<()> {
+    let status = Command::new(program).arg(input).stdout(stdout).status()?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", program, status).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,148 @@
+//! Unformatted copying of inputs to stdout. On Linux the data is moved
+//! inside the kernel with copy_file_range, sendfile or splice whenever the
+//! file descriptors allow it; everywhere else, and whenever the kernel
+//! refuses, it goes through a large userspace buffer.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+
+/// Size of the buffer used when the kernel cannot copy for us.
+pub const BUFFER_SIZE: usize = 128 * 1024;
+
+/// A handle on stdout that bypasses the standard library's line buffering.
+#[cfg(unix)]
+pub fn stdout() -> io::Result<File> {
+    use std::os::fd::AsFd;
+    io::stdout().as_fd().try_clone_to_owned().map(File::from)
+}
+
+/// A handle on stdout that bypasses the standard library's line buffering.
+#[cfg(windows)]
+pub fn stdout() -> io::Result<File> {
+    use std::os::windows::io::AsHandle;
+    io::stdout()
+        .as_handle()
+        .try_clone_to_owned()
+        .map(File::from)
+}
+
+/// Copies everything left in `input` to `output`, returning the number of
+/// bytes copied.
+pub fn copy(input: &mut File, output: &mut File) -> io::Result<u64> {
+    #[cfg(target_os = "linux")]
+    let copied = linux::kernel_copy(input, output)?;
+    #[cfg(not(target_os = "linux"))]
+    let copied = 0;
+
+    Ok(copied + buffered_copy(input, output)?)
+}
+
+/// Copies `input` to `output` through a single reusable buffer.
+pub fn buffered_copy(input: &mut impl Read, output: &mut impl Write) -> io::Result<u64> {
+    let mut buffer = vec![0; BUFFER_SIZE];
+    let mut copied = 0;
+    loop {
+        let bytes = match input.read(&mut buffer) {
+            Ok(0) => return Ok(copied),
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        output.write_all(&buffer[..bytes])?;
+        copied += bytes as u64;
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::fs::File;
+    use std::io;
+    use std::os::fd::AsRawFd;
+    use std::ptr;
+
+    /// Most bytes handed to the kernel in one call.
+    const CHUNK_SIZE: usize = 1 << 30;
+
+    #[derive(Clone, Copy)]
+    enum Method {
+        /// File to file, possibly sharing extents on filesystems like btrfs.
+        CopyFileRange,
+        /// Regular file to anything, including pipes and sockets.
+        Sendfile,
+        /// Needs a pipe on one side, so it covers piped stdin.
+        Splice,
+    }
+
+    /// Tries each in-kernel method in turn until one copies everything.
+    /// Returns the bytes copied so far; when every method gave up the rest
+    /// is left in `input` for a userspace copy to finish.
+    pub fn kernel_copy(input: &File, output: &File) -> io::Result<u64> {
+        let mut copied = 0;
+        for method in [Method::CopyFileRange, Method::Sendfile, Method::Splice] {
+            let (bytes, finished) = copy_with(method, input, output)?;
+            copied += bytes;
+            if finished {
+                break;
+            }
+        }
+        Ok(copied)
+    }
+
+    /// Copies with `method` until end of input or until the kernel refuses.
+    /// Returns the bytes copied and whether the end of input was reached.
+    fn copy_with(method: Method, input: &File, output: &File) -> io::Result<(u64, bool)> {
+        let (in_fd, out_fd) = (input.as_raw_fd(), output.as_raw_fd());
+        let mut copied = 0;
+        loop {
+            // SAFETY: both descriptors stay open for the duration of the call
+            // and null offsets make the kernel use and update the file
+            // positions, so no memory is shared with it.
+            let result = unsafe {
+                match method {
+                    Method::CopyFileRange => libc::copy_file_range(
+                        in_fd,
+                        ptr::null_mut(),
+                        out_fd,
+                        ptr::null_mut(),
+                        CHUNK_SIZE,
+                        0,
+                    ),
+                    Method::Sendfile => libc::sendfile(out_fd, in_fd, ptr::null_mut(), CHUNK_SIZE),
+                    Method::Splice => libc::splice(
+                        in_fd,
+                        ptr::null_mut(),
+                        out_fd,
+                        ptr::null_mut(),
+                        CHUNK_SIZE,
+                        libc::SPLICE_F_MOVE,
+                    ),
+                }
+            };
+
+            match result {
+                // Files in procfs and sysfs report a size of zero, so nothing
+                // copied on the first call is not proof of an empty input;
+                // let the next method or the buffered copy decide.
+                0 if copied == 0 => return Ok((0, false)),
+                0 => return Ok((copied, true)),
+                bytes if bytes > 0 => copied += bytes as u64,
+                _ => {
+                    let e = io::Error::last_os_error();
+                    match e.raw_os_error() {
+                        Some(libc::EINTR) => continue,
+                        Some(
+                            libc::EINVAL
+                            | libc::ENOSYS
+                            | libc::EXDEV
+                            | libc::EBADF
+                            | libc::EOPNOTSUPP
+                            | libc::EPERM
+                            | libc::ESPIPE,
+                        ) => return Ok((copied, false)),
+                        _ => return Err(e),
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,38 +1,68 @@
-use clap::{parser::ValueSource, Arg, Command};
-use std::error::Error;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+mod copy;
 
-type MyResult<T> = Result<T, Box<dyn Error>>;
+use clap::{Arg, Command};
+use common::{flag, open, open_file, MyResult};
+use std::ffi::OsString;
+use std::io::{self, BufRead, Write};
 
 #[derive(Debug)]
 pub struct Config {
     files: Vec<String>,
     number_lines: bool,
     number_nonblank_lines: bool,
+    show_ends: bool,
+    show_tabs: bool,
+    show_nonprinting: bool,
+    squeeze_blank: bool,
+}
+
+impl Config {
+    /// Whether any option needs the input split into lines; without one the
+    /// bytes are copied through untouched.
+    fn formats_lines(&self) -> bool {
+        self.number_lines
+            || self.number_nonblank_lines
+            || self.show_ends
+            || self.show_tabs
+            || self.show_nonprinting
+            || self.squeeze_blank
+    }
 }
 
 pub fn run(config: Config) -> MyResult<()> {
-    for filename in config.files {
-        match open(&filename) {
+    if !config.formats_lines() {
+        return copy_files(&config.files);
+    }
+
+    let mut stdout = io::stdout().lock();
+    for filename in &config.files {
+        match open(filename) {
             Err(e) => eprintln!("Failed to open {}: {}", filename, e),
-            Ok(file) => {
+            Ok(mut file) => {
                 let mut last_number = 0;
-                for (line_number, line) in file.lines().enumerate() {
-                    let line = line?;
-
-                    if config.number_lines {
-                        println!("{:>6}\t{}", line_number + 1, line);
-                    } else if config.number_nonblank_lines {
-                        if !line.is_empty() {
-                            last_number += 1;
-                            println!("{:>6}\t{}", last_number, line);
-                        } else {
-                            println!();
-                        }
-                    } else {
-                        println!("{}", line);
+                let mut last_blank = false;
+                let mut line = Vec::new();
+                loop {
+                    line.clear();
+                    if file.read_until(b'\n', &mut line)? == 0 {
+                        break;
+                    }
+                    let (text, newline) = match line.strip_suffix(b"\n") {
+                        Some(text) => (text, true),
+                        None => (line.as_slice(), false),
+                    };
+
+                    let blank = text.is_empty();
+                    if config.squeeze_blank && blank && last_blank {
+                        continue;
                     }
+                    last_blank = blank;
+
+                    if config.number_lines || (config.number_nonblank_lines && !blank) {
+                        last_number += 1;
+                        write!(stdout, "{:>6}\t", last_number)?;
+                    }
+                    write_visible(&mut stdout, text, newline, &config)?;
                 }
             }
         }
@@ -40,14 +70,86 @@ pub fn run(config: Config) -> MyResult<()> {
     Ok(())
 }
 
-fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+/// Copies each file to stdout unchanged, letting the kernel move the data
+/// where it can.
+fn copy_files(files: &[String]) -> MyResult<()> {
+    let mut stdout = copy::stdout()?;
+    for filename in files {
+        match open_file(filename) {
+            Err(e) => eprintln!("Failed to open {}: {}", filename, e),
+            Ok(mut file) => {
+                copy::copy(&mut file, &mut stdout)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes one line with tabs, line ends and nonprinting bytes made visible
+/// as requested, byte for byte the way GNU cat does.
+fn write_visible(
+    out: &mut impl Write,
+    text: &[u8],
+    newline: bool,
+    config: &Config,
+) -> io::Result<()> {
+    // GNU cat shows the carriage return of a CRLF line end as ^M under -E
+    let (text, crlf) = match text.strip_suffix(b"\r") {
+        Some(text) if newline && config.show_ends => (text, true),
+        _ => (text, false),
+    };
+
+    if !config.show_tabs && !config.show_nonprinting {
+        out.write_all(text)?;
+    } else {
+        for &byte in text {
+            match byte {
+                b'\t' if config.show_tabs => out.write_all(b"^I")?,
+                b'\t' => out.write_all(b"\t")?,
+                _ if config.show_nonprinting => write_nonprinting(out, byte)?,
+                _ => out.write_all(&[byte])?,
+            }
+        }
+    }
+
+    if crlf {
+        out.write_all(b"^M")?;
+    }
+    if newline {
+        if config.show_ends {
+            out.write_all(b"$")?;
+        }
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Writes `byte` in cat's caret and M- notation.
+fn write_nonprinting(out: &mut impl Write, byte: u8) -> io::Result<()> {
+    let byte = if byte >= 128 {
+        out.write_all(b"M-")?;
+        byte - 128
+    } else {
+        byte
+    };
+    match byte {
+        0..=31 => out.write_all(&[b'^', byte + 64]),
+        127 => out.write_all(b"^?"),
+        _ => out.write_all(&[byte]),
     }
 }
 
 pub fn get_args() -> MyResult<Config> {
+    get_args_from(std::env::args_os())
+}
+
+/// Like `get_args`, but parses `args` (whose first item is the program name)
+/// instead of the process arguments.
+pub fn get_args_from<I, T>(args: I) -> MyResult<Config>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
     let mut matches = Command::new("catr")
         .version("0.1.0")
         .author("Derek Warner <derekw3@illinois.edu>")
@@ -73,23 +175,73 @@ pub fn get_args() -> MyResult<Config> {
                 .help("Number lines excluding blank lines")
                 .num_args(0),
         )
-        .get_matches();
+        .arg(
+            Arg::new("show_all")
+                .short('A')
+                .long("show-all")
+                .help("Equivalent to -vET")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("show_nonprinting_ends")
+                .short('e')
+                .help("Equivalent to -vE")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("show_ends")
+                .short('E')
+                .long("show-ends")
+                .help("Display $ at the end of each line")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("show_nonprinting_tabs")
+                .short('t')
+                .help("Equivalent to -vT")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("show_tabs")
+                .short('T')
+                .long("show-tabs")
+                .help("Display TAB characters as ^I")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("show_nonprinting")
+                .short('v')
+                .long("show-nonprinting")
+                .help("Use ^ and M- notation, except for LFD and TAB")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("squeeze_blank")
+                .short('s')
+                .long("squeeze-blank")
+                .help("Suppress repeated empty output lines")
+                .num_args(0),
+        )
+        .get_matches_from(args);
 
     let files_vec: Vec<String> = matches.remove_many("files").unwrap().collect();
 
-    let number: bool = matches!(
-        matches.value_source("number_lines").unwrap(),
-        ValueSource::CommandLine
-    );
-
-    let number_nonblank: bool = matches!(
-        matches.value_source("number_nonblank_lines").unwrap(),
-        ValueSource::CommandLine
-    );
+    let number = flag(&matches, "number_lines");
+    let number_nonblank = flag(&matches, "number_nonblank_lines");
+    let show_all = flag(&matches, "show_all");
+    let show_nonprinting_ends = flag(&matches, "show_nonprinting_ends");
+    let show_nonprinting_tabs = flag(&matches, "show_nonprinting_tabs");
 
     Ok(Config {
         files: files_vec,
         number_lines: number,
         number_nonblank_lines: number_nonblank,
+        show_ends: show_all || show_nonprinting_ends || flag(&matches, "show_ends"),
+        show_tabs: show_all || show_nonprinting_tabs || flag(&matches, "show_tabs"),
+        show_nonprinting: show_all
+            || show_nonprinting_ends
+            || show_nonprinting_tabs
+            || flag(&matches, "show_nonprinting"),
+        squeeze_blank: flag(&matches, "squeeze_blank"),
     })
 }
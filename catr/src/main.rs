@@ -1,6 +1,6 @@
 fn main() {
     if let Err(e) = catr::get_args().and_then(catr::run) {
         eprintln!("{}", e);
-        std::process::exit(1);
+        std::process::exit(e.exit_code());
     }
 }
@@ -11,6 +11,8 @@ const EMPTY: &str = "tests/inputs/empty.txt";
 const FOX: &str = "tests/inputs/fox.txt";
 const SPIDERS: &str = "tests/inputs/spiders.txt";
 const BUSTLE: &str = "tests/inputs/the-bustle.txt";
+const CONTROLS: &str = "tests/inputs/controls.txt";
+const BINARY: &str = "tests/inputs/binary.bin";
 
 // --------------------------------------------------
 #[test]
@@ -76,6 +78,30 @@ fn run_stdin(input_file: &str, args: &[&str], expected_file: &str) -> TestResult
     Ok(())
 }
 
+// --------------------------------------------------
+fn run_bytes(args: &[&str], expected_file: &str) -> TestResult {
+    let expected = fs::read(expected_file)?;
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run_stdin_bytes(input_file: &str, args: &[&str], expected_file: &str) -> TestResult {
+    let input = fs::read(input_file)?;
+    let expected = fs::read(expected_file)?;
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn bustle_stdin() -> TestResult {
@@ -194,3 +220,304 @@ fn all_n() -> TestResult {
 fn all_b() -> TestResult {
     run(&[FOX, SPIDERS, BUSTLE, "-b"], "tests/expected/all.b.out")
 }
+
+// --------------------------------------------------
+#[test]
+fn fox_show_all() -> TestResult {
+    run(&["-A", FOX], "tests/expected/fox.txt.A.out")
+}
+
+#[test]
+fn fox_show_ends() -> TestResult {
+    run(&["-E", FOX], "tests/expected/fox.txt.E.out")
+}
+
+#[test]
+fn fox_show_tabs() -> TestResult {
+    run(&["-T", FOX], "tests/expected/fox.txt.T.out")
+}
+
+#[test]
+fn fox_show_nonprinting() -> TestResult {
+    run(&["-v", FOX], "tests/expected/fox.txt.v.out")
+}
+
+#[test]
+fn fox_squeeze_blank() -> TestResult {
+    run(&["-s", FOX], "tests/expected/fox.txt.s.out")
+}
+
+#[test]
+fn fox_e() -> TestResult {
+    run(&["-e", FOX], "tests/expected/fox.txt.e.out")
+}
+
+#[test]
+fn fox_t() -> TestResult {
+    run(&["-t", FOX], "tests/expected/fox.txt.t.out")
+}
+
+#[test]
+fn fox_ns() -> TestResult {
+    run(&["-n", "-s", FOX], "tests/expected/fox.txt.ns.out")
+}
+
+#[test]
+fn fox_b_show_ends() -> TestResult {
+    run(&["-bE", FOX], "tests/expected/fox.txt.bE.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn spiders_show_all() -> TestResult {
+    run(&["--show-all", SPIDERS], "tests/expected/spiders.txt.A.out")
+}
+
+#[test]
+fn spiders_show_ends() -> TestResult {
+    run(
+        &["--show-ends", SPIDERS],
+        "tests/expected/spiders.txt.E.out",
+    )
+}
+
+#[test]
+fn spiders_show_tabs() -> TestResult {
+    run(
+        &["--show-tabs", SPIDERS],
+        "tests/expected/spiders.txt.T.out",
+    )
+}
+
+#[test]
+fn spiders_show_nonprinting() -> TestResult {
+    run(
+        &["--show-nonprinting", SPIDERS],
+        "tests/expected/spiders.txt.v.out",
+    )
+}
+
+#[test]
+fn spiders_squeeze_blank() -> TestResult {
+    run(
+        &["--squeeze-blank", SPIDERS],
+        "tests/expected/spiders.txt.s.out",
+    )
+}
+
+#[test]
+fn spiders_e() -> TestResult {
+    run(&["-e", SPIDERS], "tests/expected/spiders.txt.e.out")
+}
+
+#[test]
+fn spiders_t() -> TestResult {
+    run(&["-t", SPIDERS], "tests/expected/spiders.txt.t.out")
+}
+
+#[test]
+fn spiders_ns() -> TestResult {
+    run(&["-n", "-s", SPIDERS], "tests/expected/spiders.txt.ns.out")
+}
+
+#[test]
+fn spiders_b_show_ends() -> TestResult {
+    run(&["-bE", SPIDERS], "tests/expected/spiders.txt.bE.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_show_all() -> TestResult {
+    run(&["-A", BUSTLE], "tests/expected/the-bustle.txt.A.out")
+}
+
+#[test]
+fn bustle_show_ends() -> TestResult {
+    run(&["-E", BUSTLE], "tests/expected/the-bustle.txt.E.out")
+}
+
+#[test]
+fn bustle_show_tabs() -> TestResult {
+    run(&["-T", BUSTLE], "tests/expected/the-bustle.txt.T.out")
+}
+
+#[test]
+fn bustle_show_nonprinting() -> TestResult {
+    run(&["-v", BUSTLE], "tests/expected/the-bustle.txt.v.out")
+}
+
+#[test]
+fn bustle_squeeze_blank() -> TestResult {
+    run(&["-s", BUSTLE], "tests/expected/the-bustle.txt.s.out")
+}
+
+#[test]
+fn bustle_e() -> TestResult {
+    run(&["-e", BUSTLE], "tests/expected/the-bustle.txt.e.out")
+}
+
+#[test]
+fn bustle_t() -> TestResult {
+    run(&["-t", BUSTLE], "tests/expected/the-bustle.txt.t.out")
+}
+
+#[test]
+fn bustle_ns() -> TestResult {
+    run(
+        &["-n", "-s", BUSTLE],
+        "tests/expected/the-bustle.txt.ns.out",
+    )
+}
+
+#[test]
+fn bustle_b_show_ends() -> TestResult {
+    run(&["-bE", BUSTLE], "tests/expected/the-bustle.txt.bE.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn controls_show_all() -> TestResult {
+    run(&["-A", CONTROLS], "tests/expected/controls.txt.A.out")
+}
+
+#[test]
+fn controls_show_ends() -> TestResult {
+    run(&["-E", CONTROLS], "tests/expected/controls.txt.E.out")
+}
+
+#[test]
+fn controls_show_tabs() -> TestResult {
+    run(&["-T", CONTROLS], "tests/expected/controls.txt.T.out")
+}
+
+#[test]
+fn controls_show_nonprinting() -> TestResult {
+    run(&["-v", CONTROLS], "tests/expected/controls.txt.v.out")
+}
+
+#[test]
+fn controls_squeeze_blank() -> TestResult {
+    run(&["-s", CONTROLS], "tests/expected/controls.txt.s.out")
+}
+
+#[test]
+fn controls_e() -> TestResult {
+    run(&["-e", CONTROLS], "tests/expected/controls.txt.e.out")
+}
+
+#[test]
+fn controls_t() -> TestResult {
+    run(&["-t", CONTROLS], "tests/expected/controls.txt.t.out")
+}
+
+#[test]
+fn controls_ns() -> TestResult {
+    run(
+        &["-n", "-s", CONTROLS],
+        "tests/expected/controls.txt.ns.out",
+    )
+}
+
+#[test]
+fn controls_b_show_ends() -> TestResult {
+    run(&["-bE", CONTROLS], "tests/expected/controls.txt.bE.out")
+}
+
+#[test]
+fn controls_n_show_all() -> TestResult {
+    run(&["-nA", CONTROLS], "tests/expected/controls.txt.nA.out")
+}
+
+#[test]
+fn controls() -> TestResult {
+    run(&[CONTROLS], "tests/expected/controls.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn binary() -> TestResult {
+    run_bytes(&[BINARY], BINARY)
+}
+
+#[test]
+fn binary_stdin() -> TestResult {
+    run_stdin_bytes(BINARY, &[], BINARY)
+}
+
+#[test]
+fn binary_n() -> TestResult {
+    run_bytes(&["-n", BINARY], "tests/expected/binary.bin.n.out")
+}
+
+#[test]
+fn binary_b() -> TestResult {
+    run_bytes(&["-b", BINARY], "tests/expected/binary.bin.b.out")
+}
+
+#[test]
+fn binary_show_nonprinting() -> TestResult {
+    run_bytes(&["-v", BINARY], "tests/expected/binary.bin.v.out")
+}
+
+#[test]
+fn binary_show_ends() -> TestResult {
+    run_bytes(&["-E", BINARY], "tests/expected/binary.bin.E.out")
+}
+
+#[test]
+fn binary_show_all() -> TestResult {
+    run_bytes(&["-A", BINARY], "tests/expected/binary.bin.A.out")
+}
+
+#[test]
+fn binary_squeeze_blank() -> TestResult {
+    run_bytes(&["-s", BINARY], "tests/expected/binary.bin.s.out")
+}
+
+#[test]
+fn binary_n_stdin() -> TestResult {
+    run_stdin_bytes(BINARY, &["-n"], "tests/expected/binary.bin.n.out")
+}
+
+// --------------------------------------------------
+fn gen_output_file() -> std::path::PathBuf {
+    loop {
+        let path = std::env::temp_dir().join(gen_bad_file());
+        if fs::metadata(&path).is_err() {
+            return path;
+        }
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_to_file() -> TestResult {
+    let output = gen_output_file();
+    let status = std::process::Command::new(assert_cmd::cargo::cargo_bin(PRG))
+        .args([BINARY, FOX])
+        .stdout(fs::File::create(&output)?)
+        .status()?;
+    assert!(status.success());
+
+    let mut expected = fs::read(BINARY)?;
+    expected.extend(fs::read(FOX)?);
+    assert_eq!(fs::read(&output)?, expected);
+    fs::remove_file(&output)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_file_stdin_to_file() -> TestResult {
+    let output = gen_output_file();
+    let status = std::process::Command::new(assert_cmd::cargo::cargo_bin(PRG))
+        .arg("-")
+        .stdin(fs::File::open(BINARY)?)
+        .stdout(fs::File::create(&output)?)
+        .status()?;
+    assert!(status.success());
+
+    assert_eq!(fs::read(&output)?, fs::read(BINARY)?);
+    fs::remove_file(&output)?;
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use clap::{value_parser, Arg, Command};
+use common::{flag, Error, MyResult, EXIT_SUCCESS};
+use std::ffi::OsString;
+use std::io;
+use std::path::Path;
+
+#[cfg(unix)]
+use std::os::unix::fs::symlink;
+#[cfg(windows)]
+use std::os::windows::fs::symlink_file as symlink;
+
+/// Entry point of a utility, given its arguments with the program name first,
+/// returning the status to exit with.
+type Applet = fn(Vec<OsString>) -> MyResult<i32>;
+
+/// Every utility built into clu, by its own name and the name of the tool it
+/// stands in for.
+const APPLETS: &[(&str, &str, Applet)] = &[
+    ("catr", "cat", |args| {
+        catr::get_args_from(args)
+            .and_then(catr::run)
+            .map(|()| EXIT_SUCCESS)
+    }),
+    ("cutr", "cut", |args| {
+        cutr::get_args_from(args)
+            .and_then(cutr::run)
+            .map(|()| EXIT_SUCCESS)
+    }),
+    ("echor", "echo", |args| {
+        echor::get_args_from(args)
+            .and_then(|config| echor::run(config, &mut io::stdout()))
+            .map(|()| EXIT_SUCCESS)
+    }),
+    ("grepr", "grep", |args| {
+        grepr::get_args_from(args).and_then(grepr::run)
+    }),
+    ("headr", "head", |args| {
+        headr::get_args_from(args)
+            .and_then(headr::run)
+            .map(|()| EXIT_SUCCESS)
+    }),
+    ("sortr", "sort", |args| {
+        sortr::get_args_from(args)
+            .and_then(sortr::run)
+            .map(|()| EXIT_SUCCESS)
+    }),
+    ("tailr", "tail", |args| {
+        tailr::get_args_from(args)
+            .and_then(tailr::run)
+            .map(|()| EXIT_SUCCESS)
+    }),
+    ("uniqr", "uniq", |args| {
+        uniqr::get_args_from(args)
+            .and_then(uniqr::run)
+            .map(|()| EXIT_SUCCESS)
+    }),
+    ("wcr", "wc", |args| {
+        wcr::get_args_from(args)
+            .and_then(wcr::run)
+            .map(|()| EXIT_SUCCESS)
+    }),
+];
+
+fn find_applet(name: &str) -> Option<Applet> {
+    APPLETS
+        .iter()
+        .find(|(applet, alias, _)| name == *applet || name == *alias)
+        .map(|(_, _, run)| *run)
+}
+
+/// Runs the utility named by the program name in `args`, or, when invoked as
+/// clu itself, the one named by the first argument. Returns the status to
+/// exit with.
+pub fn run(args: Vec<OsString>) -> MyResult<i32> {
+    let invoked = args
+        .first()
+        .and_then(|arg0| Path::new(arg0).file_stem())
+        .and_then(|name| name.to_str())
+        .and_then(find_applet);
+    if let Some(applet) = invoked {
+        return applet(args);
+    }
+
+    let mut matches = Command::new("clu")
+        .version("0.1.0")
+        .author("Derek Warner <derekw3@illinois.edu>")
+        .about("Rusty command line utilities in a single binary")
+        .arg(
+            Arg::new("install")
+                .long("install")
+                .value_name("DIR")
+                .help("Create symlinks in DIR for every utility and its alias")
+                .num_args(1)
+                .exclusive(true),
+        )
+        .arg(
+            Arg::new("list")
+                .long("list")
+                .help("List the available utilities")
+                .num_args(0)
+                .exclusive(true),
+        )
+        .subcommand_value_name("UTILITY")
+        .allow_external_subcommands(true)
+        .external_subcommand_value_parser(value_parser!(OsString))
+        .arg_required_else_help(true)
+        .get_matches_from(args);
+
+    if let Some(dir) = matches.remove_one::<String>("install") {
+        install(Path::new(&dir))?;
+        return Ok(EXIT_SUCCESS);
+    }
+
+    if flag(&matches, "list") {
+        for (name, _, _) in APPLETS {
+            println!("{}", name);
+        }
+        return Ok(EXIT_SUCCESS);
+    }
+
+    match matches.remove_subcommand() {
+        Some((name, mut sub_matches)) => {
+            let applet = find_applet(&name)
+                .ok_or_else(|| Error::Usage(format!("unknown utility -- {}", name)))?;
+            let mut applet_args = vec![OsString::from(name)];
+            if let Some(rest) = sub_matches.remove_many::<OsString>("") {
+                applet_args.extend(rest);
+            }
+            applet(applet_args)
+        }
+        None => Ok(EXIT_SUCCESS),
+    }
+}
+
+/// Links every utility name and alias in `dir` to the running executable,
+/// leaving any existing entries alone.
+fn install(dir: &Path) -> MyResult<()> {
+    let exe = std::env::current_exe()?;
+    let names = APPLETS.iter().flat_map(|(name, alias, _)| [name, alias]);
+    for name in names {
+        let link = dir.join(name);
+        if link.symlink_metadata().is_ok() {
+            eprintln!("{}: already exists", link.display());
+            continue;
+        }
+        symlink(&exe, &link).map_err(|e| Error::File {
+            path: link.display().to_string(),
+            source: e,
+        })?;
+    }
+    Ok(())
+}
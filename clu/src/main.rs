@@ -0,0 +1,9 @@
+fn main() {
+    match clu::run(std::env::args_os().collect()) {
+        Ok(status) => std::process::exit(status),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(e.exit_code());
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use rand::{distributions::Alphanumeric, Rng};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+const PRG: &str = "clu";
+const FOX: &str = "../catr/tests/inputs/fox.txt";
+const TEN: &str = "../headr/tests/inputs/ten.txt";
+const TYGER: &str = "../wcr/tests/inputs/tyger.txt";
+
+// --------------------------------------------------
+#[test]
+fn usage() -> TestResult {
+    for flag in &["-h", "--help"] {
+        Command::cargo_bin(PRG)?
+            .arg(flag)
+            .assert()
+            .stdout(predicate::str::contains("Usage"));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn lists_utilities() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .arg("--list")
+        .assert()
+        .success()
+        .stdout("catr\ncutr\nechor\ngrepr\nheadr\nsortr\ntailr\nuniqr\nwcr\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_unknown_utility() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["nope", "-n"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown utility -- nope"));
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> TestResult {
+    let expected = fs::read_to_string(expected_file)?;
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn cat() -> TestResult {
+    run(&["cat", "-n", FOX], "../catr/tests/expected/fox.txt.n.out")
+}
+
+#[test]
+fn catr() -> TestResult {
+    run(&["catr", "-b", FOX], "../catr/tests/expected/fox.txt.b.out")
+}
+
+#[test]
+fn echo() -> TestResult {
+    run(
+        &["echo", "-n", "Hello", "there"],
+        "../echor/tests/expected/hello2.n.txt",
+    )
+}
+
+#[test]
+fn cut() -> TestResult {
+    run(
+        &["cut", "--csv", "-f", "3", "../cutr/tests/inputs/books.csv"],
+        "../cutr/tests/expected/books.csv.csv.f3.out",
+    )
+}
+
+#[test]
+fn grep() -> TestResult {
+    run(
+        &["grep", "-i", "the", "../grepr/tests/inputs/the-bustle.txt"],
+        "../grepr/tests/expected/the-bustle.txt.the.i.out",
+    )
+}
+
+#[test]
+fn grep_no_match() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["grep", "foo", "../grepr/tests/inputs/empty.txt"])
+        .assert()
+        .code(1)
+        .stdout("");
+    Ok(())
+}
+
+#[test]
+fn grep_bad_pattern() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["grep", "*foo", FOX])
+        .assert()
+        .code(2)
+        .stderr("illegal pattern -- *foo\n");
+    Ok(())
+}
+
+#[test]
+fn grep_unreadable() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["grep", "fox", "../grepr/tests/inputs", FOX])
+        .assert()
+        .code(2)
+        .stdout(format!(
+            "{}:The quick brown fox jumps over the lazy dog.\n",
+            FOX
+        ));
+    Ok(())
+}
+
+#[test]
+fn head() -> TestResult {
+    run(
+        &["head", "-n", "2", TEN],
+        "../headr/tests/expected/ten.txt.n2.out",
+    )
+}
+
+#[test]
+fn sort() -> TestResult {
+    run(
+        &["sort", "-n", "../sortr/tests/inputs/numbers.txt"],
+        "../sortr/tests/expected/numbers.txt.n.out",
+    )
+}
+
+#[test]
+fn tail() -> TestResult {
+    run(
+        &["tail", "-n", "+2", TEN],
+        "../tailr/tests/expected/ten.txt.n+2.out",
+    )
+}
+
+#[test]
+fn uniq() -> TestResult {
+    run(
+        &["uniq", "-c", "../uniqr/tests/inputs/repeats.txt"],
+        "../uniqr/tests/expected/repeats.txt.c.out",
+    )
+}
+
+#[test]
+fn wc() -> TestResult {
+    let input = fs::read_to_string(TYGER)?;
+    let expected = fs::read_to_string("../wcr/tests/expected/tyger.txt.l.stdin.out")?;
+    Command::cargo_bin(PRG)?
+        .args(["wc", "-l"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+fn gen_install_dir() -> PathBuf {
+    loop {
+        let name: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(7)
+            .map(char::from)
+            .collect();
+        let dir = std::env::temp_dir().join(format!("clu-{}", name));
+        if fs::metadata(&dir).is_err() {
+            return dir;
+        }
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn installs_links() -> TestResult {
+    let dir = gen_install_dir();
+    fs::create_dir(&dir)?;
+
+    Command::cargo_bin(PRG)?
+        .arg("--install")
+        .arg(&dir)
+        .assert()
+        .success();
+
+    let expected = fs::read_to_string("../catr/tests/expected/fox.txt.n.out")?;
+    for name in ["catr", "cat"] {
+        Command::new(dir.join(name))
+            .args(["-n", FOX])
+            .assert()
+            .success()
+            .stdout(expected.clone());
+    }
+    for name in [
+        "cutr", "cut", "echor", "echo", "grepr", "grep", "headr", "head", "sortr", "sort", "tailr",
+        "tail", "uniqr", "uniq", "wcr", "wc",
+    ] {
+        assert!(dir.join(name).symlink_metadata().is_ok(), "{}", name);
+    }
+
+    Command::cargo_bin(PRG)?
+        .arg("--install")
+        .arg(&dir)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("already exists"));
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_install_missing_dir() -> TestResult {
+    let dir = gen_install_dir();
+    Command::cargo_bin(PRG)?
+        .arg("--install")
+        .arg(&dir)
+        .assert()
+        .failure()
+        .stderr(predicate::str::is_match("catr: .* [(]os error 2[)]")?);
+    Ok(())
+}
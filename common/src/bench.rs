@@ -0,0 +1,63 @@
+//! The harness the throughput benchmarks share: a generated input of a
+//! size taken from the environment and the best rate out of a few runs.
+
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+pub type BenchResult<T> = Result<T, Box<dyn Error>>;
+
+/// Times each benchmark is run, keeping the fastest.
+pub const RUNS: usize = 3;
+
+/// The input size in MiB given in the environment variable `var`, 256 by
+/// default.
+pub fn megabytes(var: &str) -> u64 {
+    env::var(var)
+        .ok()
+        .and_then(|mb| mb.parse().ok())
+        .unwrap_or(256)
+}
+
+/// Fills `path` with at least `size` bytes of the lines `line` makes from
+/// their numbers, counting from zero.
+pub fn write_input(path: &Path, size: u64, mut line: impl FnMut(u64) -> String) -> BenchResult<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    let mut written = 0;
+    let mut number = 0;
+    while written < size {
+        let line = line(number);
+        out.write_all(line.as_bytes())?;
+        written += line.len() as u64;
+        number += 1;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Whether `program` can be run, so tools to compare against that are not
+/// installed are left out.
+pub fn installed(program: &str) -> bool {
+    Command::new(program).arg("/dev/null").output().is_ok()
+}
+
+/// Runs `bench` `RUNS` times and prints its best rate over `size` bytes.
+pub fn report(
+    name: &str,
+    size: u64,
+    mut bench: impl FnMut() -> BenchResult<()>,
+) -> BenchResult<()> {
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        bench()?;
+        best = best.min(start.elapsed());
+    }
+    let rate = size as f64 / (1024.0 * 1024.0) / best.as_secs_f64();
+    println!("{:<26} {:>10.1} MiB/s", name, rate);
+    Ok(())
+}
@@ -0,0 +1,292 @@
+//! Pieces shared by every utility in the workspace: opening inputs, the
+//! error type returned from `get_args`/`run` and the exit codes the
+//! binaries report.
+
+use clap::{parser::ValueSource, ArgMatches};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+
+#[cfg(feature = "bench")]
+pub mod bench;
+
+pub type MyResult<T> = Result<T, Error>;
+
+/// Exit status for a run that did everything it was asked to.
+pub const EXIT_SUCCESS: i32 = 0;
+/// Exit status for a run that stopped on an error.
+pub const EXIT_FAILURE: i32 = 1;
+/// Exit status for a rejected option value, which GNU tools report like any
+/// other failure; clap exits with 2 for the arguments it rejects itself.
+pub const EXIT_USAGE: i32 = 1;
+/// Exit status for a run that could not read an input or write its output,
+/// in a utility whose `EXIT_FAILURE` means something else, like grep's
+/// "nothing selected".
+pub const EXIT_TROUBLE: i32 = 2;
+/// Exit status of a search, like grep's, that selected a line.
+pub const EXIT_SELECTED: i32 = EXIT_SUCCESS;
+/// Exit status of a search, like grep's, that selected nothing.
+pub const EXIT_NOT_SELECTED: i32 = 1;
+
+/// Size of the blocks read when scanning a file backwards.
+pub const BLOCK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A read or write failed.
+    Io(io::Error),
+    /// A read or write on the named file failed.
+    File { path: String, source: io::Error },
+    /// An option value was rejected, e.g. `illegal line count -- 0`.
+    Usage(String),
+    /// Any error in a utility that exits with `EXIT_TROUBLE` for it.
+    Trouble(Box<Error>),
+}
+
+impl Error {
+    /// Names the file `path` in an I/O error from opening, reading or
+    /// writing it; other errors are left as they are.
+    pub fn in_file(self, path: &str) -> Error {
+        match self {
+            Error::Io(source) => Error::File {
+                path: path.to_string(),
+                source,
+            },
+            e => e,
+        }
+    }
+
+    /// Marks the error as one the utility exits with `EXIT_TROUBLE` for.
+    pub fn trouble(self) -> Error {
+        match self {
+            Error::Trouble(_) => self,
+            e => Error::Trouble(Box::new(e)),
+        }
+    }
+
+    /// The status a binary should exit with after printing this error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Io(_) | Error::File { .. } => EXIT_FAILURE,
+            Error::Usage(_) => EXIT_USAGE,
+            Error::Trouble(_) => EXIT_TROUBLE,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::File { path, source } => write!(f, "{}: {}", path, source),
+            Error::Usage(msg) => write!(f, "{}", msg),
+            Error::Trouble(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) | Error::File { source: e, .. } => Some(e),
+            Error::Usage(_) => None,
+            Error::Trouble(e) => e.source(),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// Opens `filename` for buffered reading, treating "-" as stdin.
+pub fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
+    match filename {
+        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
+        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+    }
+}
+
+/// Opens `filename` unbuffered, treating "-" as stdin. Unlike `open` this
+/// gives access to the file itself, e.g. to check whether it can be seeked.
+pub fn open_file(filename: &str) -> MyResult<File> {
+    match filename {
+        "-" => Ok(stdin_file()?),
+        _ => Ok(File::open(filename)?),
+    }
+}
+
+#[cfg(unix)]
+fn stdin_file() -> io::Result<File> {
+    use std::os::fd::AsFd;
+    io::stdin().as_fd().try_clone_to_owned().map(File::from)
+}
+
+#[cfg(windows)]
+fn stdin_file() -> io::Result<File> {
+    use std::os::windows::io::AsHandle;
+    io::stdin().as_handle().try_clone_to_owned().map(File::from)
+}
+
+/// The current position and the length of `file` when it is a regular file
+/// that can be seeked, `None` for pipes, terminals and the like.
+pub fn seekable_range(file: &mut File) -> io::Result<Option<(u64, u64)>> {
+    let metadata = file.metadata()?;
+    if !metadata.is_file() {
+        return Ok(None);
+    }
+    Ok(Some((file.stream_position()?, metadata.len())))
+}
+
+/// Offset at which the last `count` lines between `start` and `end` begin,
+/// found by reading `file` backwards one block at a time.
+pub fn start_of_last_lines(file: &mut File, start: u64, end: u64, count: usize) -> io::Result<u64> {
+    if count == 0 {
+        return Ok(end);
+    }
+
+    let mut buffer = vec![0; BLOCK_SIZE];
+    let mut found = 0;
+    let mut position = end;
+    while position > start {
+        let size = (position - start).min(BLOCK_SIZE as u64) as usize;
+        position -= size as u64;
+        file.seek(SeekFrom::Start(position))?;
+        file.read_exact(&mut buffer[..size])?;
+
+        for i in (0..size).rev() {
+            let offset = position + i as u64;
+            // The newline ending the file closes the last line, it does not
+            // separate it from another one
+            if buffer[i] == b'\n' && offset + 1 != end {
+                found += 1;
+                if found == count {
+                    return Ok(offset + 1);
+                }
+            }
+        }
+    }
+    Ok(start)
+}
+
+/// Parses a decimal count with an optional GNU size suffix: `b` (512),
+/// `K`, `M`, `G`, `T`, `P`, `E`, `Z` and `Y` (powers of 1024, also written
+/// `KiB`, `MiB`, ...) and `kB`, `MB`, ... (powers of 1000). `k` and `m` are
+/// accepted for `K` and `M`. The error message is the rejected value, so
+/// callers can wrap it as in `illegal byte count -- 4X`.
+pub fn parse_size(val: &str) -> MyResult<u64> {
+    let invalid = || Error::Usage(val.to_string());
+    let too_large = || Error::Usage(format!("{}: Value too large for defined data type", val));
+
+    let unsigned = val.strip_prefix('+').unwrap_or(val);
+    let digits = unsigned
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(unsigned.len());
+    let (number, suffix) = unsigned.split_at(digits);
+    if number.is_empty() {
+        return Err(invalid());
+    }
+    // Only digits remain, so the parse can fail on overflow alone
+    let number: u64 = number.parse().map_err(|_| too_large())?;
+
+    let multiplier = match suffix {
+        "" => 1,
+        "b" => 512,
+        _ => {
+            let mut chars = suffix.chars();
+            let power = match chars.next() {
+                Some('k' | 'K') => 1,
+                Some('m' | 'M') => 2,
+                Some('G') => 3,
+                Some('T') => 4,
+                Some('P') => 5,
+                Some('E') => 6,
+                Some('Z') => 7,
+                Some('Y') => 8,
+                _ => return Err(invalid()),
+            };
+            let base: u64 = match chars.as_str() {
+                "" | "iB" => 1024,
+                "B" => 1000,
+                _ => return Err(invalid()),
+            };
+            base.checked_pow(power).ok_or_else(too_large)?
+        }
+    };
+    number.checked_mul(multiplier).ok_or_else(too_large)
+}
+
+/// Whether the flag `id` was given on the command line rather than left at
+/// its default.
+pub fn flag(matches: &ArgMatches, id: &str) -> bool {
+    matches!(matches.value_source(id), Some(ValueSource::CommandLine))
+}
+
+#[test]
+fn test_open_missing_file() {
+    let result = open("this/file/does/not/exist");
+    assert!(result.is_err());
+    let e = result.err().unwrap();
+    assert!(matches!(e, Error::Io(_)));
+    assert!(e.to_string().ends_with("(os error 2)"));
+    assert_eq!(e.exit_code(), EXIT_FAILURE);
+}
+
+#[test]
+fn test_in_file() {
+    let e = open("this/file/does/not/exist")
+        .err()
+        .unwrap()
+        .in_file("missing");
+    assert!(matches!(e, Error::File { ref path, .. } if path == "missing"));
+    assert!(e.to_string().starts_with("missing: "));
+    let usage = Error::Usage("illegal count -- 0".to_string()).in_file("missing");
+    assert!(matches!(usage, Error::Usage(_)));
+}
+
+#[test]
+fn test_exit_code() {
+    let usage = Error::Usage("illegal line count -- 0".to_string());
+    assert_eq!(usage.exit_code(), EXIT_USAGE);
+    let trouble = usage.trouble().trouble();
+    assert_eq!(trouble.exit_code(), EXIT_TROUBLE);
+    assert_eq!(trouble.to_string(), "illegal line count -- 0");
+    assert!(matches!(trouble, Error::Trouble(e) if matches!(*e, Error::Usage(_))));
+}
+
+#[test]
+fn test_parse_size() {
+    assert_eq!(parse_size("3").unwrap(), 3);
+    assert_eq!(parse_size("+3").unwrap(), 3);
+    assert_eq!(parse_size("0").unwrap(), 0);
+    assert_eq!(parse_size("2b").unwrap(), 1024);
+    assert_eq!(parse_size("4K").unwrap(), 4096);
+    assert_eq!(parse_size("2k").unwrap(), 2048);
+    assert_eq!(parse_size("4KiB").unwrap(), 4096);
+    assert_eq!(parse_size("4kB").unwrap(), 4000);
+    assert_eq!(parse_size("1MiB").unwrap(), 1 << 20);
+    assert_eq!(parse_size("1m").unwrap(), 1 << 20);
+    assert_eq!(parse_size("3MB").unwrap(), 3_000_000);
+    assert_eq!(parse_size("1G").unwrap(), 1 << 30);
+    assert_eq!(parse_size("1GB").unwrap(), 1_000_000_000);
+    assert_eq!(parse_size("15E").unwrap(), 15 << 60);
+
+    for bad in [
+        "", "K", "-1", "1.5K", "0x10", "1g", "1B", "1Ki", "1KiBx", " 1",
+    ] {
+        let result = parse_size(bad);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), bad.to_string());
+    }
+
+    for huge in ["18446744073709551616", "16E", "1Z", "1Y"] {
+        let result = parse_size(huge);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!("{}: Value too large for defined data type", huge)
+        );
+    }
+}
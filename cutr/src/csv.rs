@@ -0,0 +1,137 @@
+//! `--csv`: fields as RFC 4180 has them. A field may be quoted, so the
+//! delimiter, quotes (written twice) and even newlines can appear inside
+//! it. Selected fields are printed unquoted unless they hold the output
+//! delimiter, a quote or a line break, in which case they are quoted again.
+
+/// A record being read, which may take several lines when a quoted field
+/// holds line breaks. Each byte is looked at once however many lines the
+/// record takes.
+#[derive(Default)]
+pub struct Record {
+    fields: Vec<Vec<u8>>,
+    field: Vec<u8>,
+    state: State,
+}
+
+#[derive(Default, PartialEq)]
+enum State {
+    /// At the start of a field, where a quote opens a quoted field.
+    #[default]
+    FieldStart,
+    Unquoted,
+    Quoted,
+    /// Just after a quote in a quoted field, which either ends the field
+    /// or, followed by another, stands for a quote.
+    QuoteInQuoted,
+}
+
+impl Record {
+    /// Reads `bytes`, the next part of the record, removing the quoting.
+    pub fn extend(&mut self, bytes: &[u8], delimiter: &[u8]) {
+        let mut i = 0;
+        while i < bytes.len() {
+            let byte = bytes[i];
+            match self.state {
+                State::Quoted => match byte {
+                    b'"' => self.state = State::QuoteInQuoted,
+                    _ => self.field.push(byte),
+                },
+                State::QuoteInQuoted if byte == b'"' => {
+                    self.field.push(b'"');
+                    self.state = State::Quoted;
+                }
+                State::FieldStart if byte == b'"' => self.state = State::Quoted,
+                _ if bytes[i..].starts_with(delimiter) => {
+                    self.fields.push(std::mem::take(&mut self.field));
+                    self.state = State::FieldStart;
+                    i += delimiter.len();
+                    continue;
+                }
+                _ => {
+                    self.field.push(byte);
+                    self.state = State::Unquoted;
+                }
+            }
+            i += 1;
+        }
+    }
+
+    /// Whether a quoted field is still open, i.e. the record goes on in
+    /// the next line.
+    pub fn is_open(&self) -> bool {
+        self.state == State::Quoted
+    }
+
+    pub fn into_fields(mut self) -> Vec<Vec<u8>> {
+        self.fields.push(self.field);
+        self.fields
+    }
+}
+
+/// Quotes `field` when it could not be read back otherwise.
+pub fn quote(field: &[u8], delimiter: &[u8]) -> Vec<u8> {
+    let needs_quotes = field.iter().any(|b| matches!(b, b'"' | b'\n' | b'\r'))
+        || (!delimiter.is_empty() && field.windows(delimiter.len()).any(|w| w == delimiter));
+    if !needs_quotes {
+        return field.to_vec();
+    }
+    let mut quoted = vec![b'"'];
+    for &byte in field {
+        if byte == b'"' {
+            quoted.push(b'"');
+        }
+        quoted.push(byte);
+    }
+    quoted.push(b'"');
+    quoted
+}
+
+#[test]
+fn test_split() {
+    fn split(record: &[u8], delimiter: &[u8]) -> (Vec<Vec<u8>>, bool) {
+        let mut fields = Record::default();
+        fields.extend(record, delimiter);
+        let closed = !fields.is_open();
+        (fields.into_fields(), closed)
+    }
+    let fields = |record: &[u8]| split(record, b",");
+    assert_eq!(fields(b""), (vec![vec![]], true));
+    assert_eq!(fields(b"a,b"), (vec![b"a".to_vec(), b"b".to_vec()], true));
+    assert_eq!(
+        fields(b"\"a,b\",\"say \"\"hi\"\"\","),
+        (vec![b"a,b".to_vec(), b"say \"hi\"".to_vec(), vec![]], true)
+    );
+    // Quotes only count at the start of a field
+    assert_eq!(
+        fields(b"a\"b,c"),
+        (vec![b"a\"b".to_vec(), b"c".to_vec()], true)
+    );
+    assert_eq!(
+        fields(b"a,\"b\nc"),
+        (vec![b"a".to_vec(), b"b\nc".to_vec()], false)
+    );
+    assert_eq!(
+        split(b"a::\"b::c\"::d", b"::"),
+        (vec![b"a".to_vec(), b"b::c".to_vec(), b"d".to_vec()], true)
+    );
+
+    // A record read a line at a time, splitting a doubled quote
+    let mut record = Record::default();
+    for part in [&b"a,\"b\""[..], b"\"\n", b"c\",d"] {
+        record.extend(part, b",");
+    }
+    assert!(!record.is_open());
+    assert_eq!(
+        record.into_fields(),
+        vec![b"a".to_vec(), b"b\"\nc".to_vec(), b"d".to_vec()]
+    );
+}
+
+#[test]
+fn test_quote() {
+    assert_eq!(quote(b"plain", b","), b"plain");
+    assert_eq!(quote(b"a,b", b","), b"\"a,b\"");
+    assert_eq!(quote(b"a,b", b"\t"), b"a,b");
+    assert_eq!(quote(b"say \"hi\"", b","), b"\"say \"\"hi\"\"\"");
+    assert_eq!(quote(b"two\nlines", b","), b"\"two\nlines\"");
+}
@@ -0,0 +1,446 @@
+use clap::{Arg, ArgGroup, Command};
+use common::{flag, open, Error, MyResult};
+use std::ffi::OsString;
+use std::io::{self, BufRead, Write};
+
+mod csv;
+
+/// Selected positions as sorted ranges of 1-based positions, inclusive;
+/// `usize::MAX` stands for the end of the line. Like GNU cut, overlapping
+/// ranges are merged but adjacent ones are not, so `1-2,3` still prints
+/// the output delimiter between 2 and 3.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Positions(Vec<(usize, usize)>);
+
+impl Positions {
+    /// The gaps between the ranges, for --complement.
+    fn complement(&self) -> Positions {
+        let mut gaps = Vec::new();
+        let mut next = 1;
+        for &(start, end) in &self.0 {
+            if start > next {
+                gaps.push((next, start - 1));
+            }
+            next = end.saturating_add(1);
+        }
+        if next != usize::MAX {
+            gaps.push((next, usize::MAX));
+        }
+        Positions(gaps)
+    }
+
+    /// Calls `select` with each of `units` that falls in a range, along
+    /// with whether it starts a new range after an earlier selected unit.
+    fn each<T>(&self, units: impl Iterator<Item = T>, mut select: impl FnMut(T, bool)) {
+        let mut ranges = self.0.iter().enumerate().peekable();
+        let mut last_range = None;
+        for (position, unit) in (1..).zip(units) {
+            while ranges.next_if(|(_, &(_, end))| end < position).is_some() {}
+            match ranges.peek() {
+                Some(&(i, &(start, _))) if start <= position => {
+                    select(unit, last_range.is_some_and(|last| last != i));
+                    last_range = Some(i);
+                }
+                Some(_) => {}
+                None => break,
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Extract {
+    Bytes(Positions),
+    Chars(Positions),
+    Fields(Positions),
+}
+
+#[derive(Debug)]
+pub struct Config {
+    files: Vec<String>,
+    extract: Extract,
+    delimiter: String,
+    output_delimiter: Option<String>,
+    only_delimited: bool,
+    csv: bool,
+}
+
+pub fn run(config: Config) -> MyResult<()> {
+    let mut stdout = io::stdout().lock();
+    for filename in &config.files {
+        match open(filename) {
+            Err(e) => eprintln!("{}: {}", filename, e),
+            Ok(mut file) => {
+                let mut line = Vec::new();
+                loop {
+                    line.clear();
+                    if file.read_until(b'\n', &mut line)? == 0 {
+                        break;
+                    }
+                    let fields = match config.csv {
+                        true => Some(read_record(&mut file, &mut line, &config, filename)?),
+                        false => None,
+                    };
+                    if line.last() == Some(&b'\n') {
+                        line.pop();
+                    }
+                    cut_line(&line, fields, &config, &mut stdout)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads the fields of the CSV record starting with `line`, appending the
+/// lines a quoted field runs on over to it. A quote still open at the end
+/// of the file is reported and closed there.
+fn read_record(
+    file: &mut impl BufRead,
+    line: &mut Vec<u8>,
+    config: &Config,
+    filename: &str,
+) -> io::Result<Vec<Vec<u8>>> {
+    let delimiter = config.delimiter.as_bytes();
+    let mut record = csv::Record::default();
+    let mut start = 0;
+    loop {
+        let newline = line.last() == Some(&b'\n');
+        record.extend(&line[start..line.len() - newline as usize], delimiter);
+        if !record.is_open() {
+            break;
+        }
+        start = line.len();
+        if !newline || file.read_until(b'\n', line)? == 0 {
+            eprintln!("{}: unterminated quoted field", filename);
+            break;
+        }
+        // The line break is part of the quoted field
+        record.extend(b"\n", delimiter);
+    }
+    Ok(record.into_fields())
+}
+
+/// Prints the selected parts of `line`, which has no newline. `fields`
+/// holds the fields of a CSV record, already split.
+fn cut_line(
+    line: &[u8],
+    fields: Option<Vec<Vec<u8>>>,
+    config: &Config,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let output_delimiter = config.output_delimiter.as_deref().unwrap_or_default();
+    let mut selected = Vec::new();
+    match &config.extract {
+        Extract::Bytes(positions) => positions.each(line.iter(), |&byte, new_range| {
+            if new_range {
+                selected.extend_from_slice(output_delimiter.as_bytes());
+            }
+            selected.push(byte);
+        }),
+        Extract::Chars(positions) => positions.each(chars(line), |c, new_range| {
+            if new_range {
+                selected.extend_from_slice(output_delimiter.as_bytes());
+            }
+            selected.extend_from_slice(c);
+        }),
+        Extract::Fields(positions) => {
+            let delimiter = config.delimiter.as_bytes();
+            let output_delimiter = config
+                .output_delimiter
+                .as_ref()
+                .unwrap_or(&config.delimiter);
+            let fields =
+                fields.unwrap_or_else(|| split(line, delimiter).map(<[u8]>::to_vec).collect());
+            // Lines without a delimiter are passed through, or left out
+            // with -s
+            if fields.len() == 1 {
+                if !config.only_delimited {
+                    out.write_all(line)?;
+                    out.write_all(b"\n")?;
+                }
+                return Ok(());
+            }
+            let mut first = true;
+            positions.each(fields.into_iter(), |field, _| {
+                if !first {
+                    selected.extend_from_slice(output_delimiter.as_bytes());
+                }
+                first = false;
+                match config.csv {
+                    true => selected.extend(csv::quote(&field, output_delimiter.as_bytes())),
+                    false => selected.extend(field),
+                }
+            });
+        }
+    }
+    selected.push(b'\n');
+    out.write_all(&selected)
+}
+
+/// Splits `line` on every occurrence of `delimiter`.
+fn split<'a>(line: &'a [u8], delimiter: &'a [u8]) -> impl Iterator<Item = &'a [u8]> {
+    let mut rest = Some(line);
+    std::iter::from_fn(move || {
+        let current = rest?;
+        match current
+            .windows(delimiter.len())
+            .position(|window| window == delimiter)
+        {
+            Some(i) => {
+                rest = Some(&current[i + delimiter.len()..]);
+                Some(&current[..i])
+            }
+            None => {
+                rest = None;
+                Some(current)
+            }
+        }
+    })
+}
+
+/// The UTF-8 characters of `line`; a byte that is not part of a valid
+/// character counts as a character of its own.
+fn chars(line: &[u8]) -> impl Iterator<Item = &[u8]> {
+    line.utf8_chunks().flat_map(|chunk| {
+        let valid = chunk.valid();
+        let invalid = chunk.invalid();
+        valid
+            .char_indices()
+            .map(move |(i, c)| &valid.as_bytes()[i..i + c.len_utf8()])
+            .chain(invalid.chunks(1))
+    })
+}
+
+/// Parses a list such as `1-3,5,7-` or `-2`: positions and ranges of
+/// positions, counting from one, separated by commas.
+pub fn parse_list(list: &str) -> MyResult<Positions> {
+    let mut ranges = Vec::new();
+    for part in list.split(',') {
+        let illegal = || Error::Usage(format!("illegal list value -- {}", part));
+        let position = |val: &str| match val.bytes().all(|b| b.is_ascii_digit()) {
+            // Positions past the end of any line all mean the same
+            true => match val.parse::<usize>() {
+                Ok(0) => Err(illegal()),
+                Ok(n) => Ok(n),
+                Err(_) if val.is_empty() => Err(illegal()),
+                Err(_) => Ok(usize::MAX - 1),
+            },
+            false => Err(illegal()),
+        };
+        let range = match part.split_once('-') {
+            None => {
+                let position = position(part)?;
+                (position, position)
+            }
+            Some(("", "")) => return Err(illegal()),
+            Some(("", end)) => (1, position(end)?),
+            Some((start, "")) => (position(start)?, usize::MAX),
+            Some((start, end)) => (position(start)?, position(end)?),
+        };
+        if range.0 > range.1 {
+            return Err(illegal());
+        }
+        ranges.push(range);
+    }
+
+    ranges.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    Ok(Positions(merged))
+}
+
+pub fn get_args() -> MyResult<Config> {
+    get_args_from(std::env::args_os())
+}
+
+/// Like `get_args`, but parses `args` (whose first item is the program name)
+/// instead of the process arguments.
+pub fn get_args_from<I, T>(args: I) -> MyResult<Config>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let mut matches = Command::new("cutr")
+        .version("0.1.0")
+        .author("Derek Warner <derekw3@illinois.edu>")
+        .about("A rusty cut")
+        .arg(
+            Arg::new("files")
+                .help("Input file(s)")
+                .default_value("-")
+                .num_args(1..),
+        )
+        .arg(
+            Arg::new("bytes")
+                .short('b')
+                .long("bytes")
+                .value_name("LIST")
+                .help("Select only these bytes")
+                .num_args(1)
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::new("chars")
+                .short('c')
+                .long("characters")
+                .value_name("LIST")
+                .help("Select only these characters")
+                .num_args(1)
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::new("fields")
+                .short('f')
+                .long("fields")
+                .value_name("LIST")
+                .help("Select only these fields")
+                .num_args(1)
+                .allow_hyphen_values(true),
+        )
+        .group(
+            ArgGroup::new("list")
+                .args(["bytes", "chars", "fields"])
+                .required(true),
+        )
+        .arg(
+            Arg::new("delimiter")
+                .short('d')
+                .long("delimiter")
+                .value_name("DELIM")
+                .help("Use DELIM instead of TAB for field delimiter")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("only_delimited")
+                .short('s')
+                .long("only-delimited")
+                .help("Do not print lines not containing delimiters")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("complement")
+                .long("complement")
+                .help("Select everything except the given bytes, characters or fields")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("output_delimiter")
+                .long("output-delimiter")
+                .value_name("STRING")
+                .help("Join the selected parts with STRING")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("csv")
+                .long("csv")
+                .help("Read fields as CSV, where quoted fields may hold the delimiter")
+                .num_args(0),
+        )
+        .get_matches_from(args);
+
+    let complement = flag(&matches, "complement");
+    let list = |id: &str| -> MyResult<Option<Positions>> {
+        matches
+            .get_one::<String>(id)
+            .map(|list| parse_list(list))
+            .transpose()
+            .map(|positions| positions.map(|p| if complement { p.complement() } else { p }))
+    };
+    let extract = match (list("bytes")?, list("chars")?, list("fields")?) {
+        (Some(positions), _, _) => Extract::Bytes(positions),
+        (_, Some(positions), _) => Extract::Chars(positions),
+        (_, _, Some(positions)) => Extract::Fields(positions),
+        _ => unreachable!("clap requires one list"),
+    };
+
+    if !matches!(extract, Extract::Fields(_)) {
+        for (id, what) in [
+            ("delimiter", "an input delimiter"),
+            ("only_delimited", "suppressing non-delimited lines"),
+            ("csv", "reading fields as CSV"),
+        ] {
+            if flag(&matches, id) {
+                return Err(Error::Usage(format!(
+                    "{} may be specified only when operating on fields",
+                    what
+                )));
+            }
+        }
+    }
+
+    let csv = flag(&matches, "csv");
+    let delimiter = matches
+        .remove_one::<String>("delimiter")
+        .unwrap_or_else(|| if csv { "," } else { "\t" }.to_string());
+    if delimiter.chars().count() != 1 {
+        return Err(Error::Usage(format!("illegal delimiter -- {}", delimiter)));
+    }
+
+    Ok(Config {
+        files: matches.remove_many("files").unwrap().collect(),
+        extract,
+        delimiter,
+        output_delimiter: matches.remove_one("output_delimiter"),
+        only_delimited: flag(&matches, "only_delimited"),
+        csv,
+    })
+}
+
+#[test]
+fn test_parse_list() {
+    let positions = |list| parse_list(list).unwrap().0;
+    assert_eq!(positions("1"), vec![(1, 1)]);
+    assert_eq!(positions("1-3,5,7-"), vec![(1, 3), (5, 5), (7, usize::MAX)]);
+    assert_eq!(positions("-2"), vec![(1, 2)]);
+    assert_eq!(positions("5,1-2"), vec![(1, 2), (5, 5)]);
+    // Overlapping ranges merge, adjacent ones stay apart
+    assert_eq!(positions("1-3,2-4,4"), vec![(1, 4)]);
+    assert_eq!(positions("1-2,3"), vec![(1, 2), (3, 3)]);
+    assert_eq!(positions("3-,1-"), vec![(1, usize::MAX)]);
+
+    for bad in [
+        "", "0", "a", "1,,2", "-", "3-1", "1-a", "+1", "1.5", "0-2", "1 ,2",
+    ] {
+        let result = parse_list(bad);
+        assert!(result.is_err(), "{:?}", bad);
+    }
+    assert_eq!(
+        parse_list("1,3-1,5").unwrap_err().to_string(),
+        "illegal list value -- 3-1"
+    );
+    assert_eq!(
+        parse_list("1,0").unwrap_err().to_string(),
+        "illegal list value -- 0"
+    );
+}
+
+#[test]
+fn test_complement() {
+    let complement = |list| parse_list(list).unwrap().complement().0;
+    assert_eq!(complement("2,4"), vec![(1, 1), (3, 3), (5, usize::MAX)]);
+    assert_eq!(complement("1-"), vec![]);
+    assert_eq!(complement("-3"), vec![(4, usize::MAX)]);
+}
+
+#[test]
+fn test_chars() {
+    let text = "a\u{e9}\u{65e5}".as_bytes().to_vec();
+    let mut invalid = text.clone();
+    invalid.insert(1, 0xff);
+    assert_eq!(chars(&text).count(), 3);
+    assert_eq!(
+        chars(&invalid).collect::<Vec<_>>(),
+        vec![
+            &b"a"[..],
+            &[0xff],
+            "\u{e9}".as_bytes(),
+            "\u{65e5}".as_bytes()
+        ]
+    );
+}
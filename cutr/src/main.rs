@@ -0,0 +1,6 @@
+fn main() {
+    if let Err(e) = cutr::get_args().and_then(cutr::run) {
+        eprintln!("{}", e);
+        std::process::exit(e.exit_code());
+    }
+}
@@ -0,0 +1,320 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use rand::{distributions::Alphanumeric, Rng};
+use std::{error::Error, fs};
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+const PRG: &str = "cutr";
+const EMPTY: &str = "tests/inputs/empty.txt";
+const TSV: &str = "tests/inputs/books.tsv";
+const CSV: &str = "tests/inputs/books.csv";
+const MIXED: &str = "tests/inputs/mixed.txt";
+const NOTES: &str = "tests/inputs/notes.csv";
+
+// --------------------------------------------------
+fn random_string() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(7)
+        .map(char::from)
+        .collect()
+}
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename = random_string();
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+fn dies(args: &[&str], expected: &str) -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(expected));
+
+    Ok(())
+}
+
+#[test]
+fn dies_no_list() -> TestResult {
+    dies(&[TSV], "the following required arguments were not provided")
+}
+
+#[test]
+fn dies_two_lists() -> TestResult {
+    dies(&["-f", "1", "-b", "1", TSV], "cannot be used with")
+}
+
+#[test]
+fn dies_bad_list() -> TestResult {
+    let bad = random_string();
+    dies(
+        &["-f", &format!("1,{}", bad), TSV],
+        &format!("illegal list value -- {}", bad),
+    )
+}
+
+#[test]
+fn dies_zero_position() -> TestResult {
+    dies(&["-b", "0", TSV], "illegal list value -- 0")
+}
+
+#[test]
+fn dies_decreasing_range() -> TestResult {
+    dies(&["-c", "1,3-1", TSV], "illegal list value -- 3-1")
+}
+
+#[test]
+fn dies_empty_range() -> TestResult {
+    dies(&["-f", "1,-", TSV], "illegal list value -- -")
+}
+
+#[test]
+fn dies_bad_delimiter() -> TestResult {
+    dies(&["-f", "1", "-d", ",,", TSV], "illegal delimiter -- ,,")
+}
+
+#[test]
+fn dies_delimiter_without_fields() -> TestResult {
+    dies(
+        &["-b", "1", "-d", ",", TSV],
+        "an input delimiter may be specified only when operating on fields",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file() -> TestResult {
+    let bad = gen_bad_file();
+    let expected = format!("{}: .* [(]os error 2[)]", bad);
+    Command::cargo_bin(PRG)?
+        .args(["-f", "1", TSV, &bad, EMPTY])
+        .assert()
+        .success()
+        .stderr(predicate::str::is_match(expected)?);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> TestResult {
+    let expected = fs::read(expected_file)?;
+
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run_stdin(args: &[&str], input_file: &str, expected_file: &str) -> TestResult {
+    let expected = fs::read(expected_file)?;
+    let input = fs::read(input_file)?;
+
+    Command::cargo_bin(PRG)?
+        .write_stdin(input)
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn empty_c() -> TestResult {
+    run(&["-c", "1-4", EMPTY], "tests/expected/empty.txt.c1-4.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_f1() -> TestResult {
+    run(&["-f", "1", TSV], "tests/expected/books.tsv.f1.out")
+}
+
+#[test]
+fn tsv_f1_stdin() -> TestResult {
+    run_stdin(&["-f", "1"], TSV, "tests/expected/books.tsv.f1.out")
+}
+
+#[test]
+fn tsv_f2_to_end() -> TestResult {
+    run(&["-f", "2-", TSV], "tests/expected/books.tsv.f2-.out")
+}
+
+#[test]
+fn tsv_f3_1() -> TestResult {
+    run(&["-f", "3,1", TSV], "tests/expected/books.tsv.f3,1.out")
+}
+
+#[test]
+fn tsv_f9() -> TestResult {
+    run(&["-f", "9", TSV], "tests/expected/books.tsv.f9.out")
+}
+
+#[test]
+fn tsv_f1_3_output_delimiter() -> TestResult {
+    run(
+        &["-f", "1,3", "--output-delimiter", " | ", TSV],
+        "tests/expected/books.tsv.f1,3.od.out",
+    )
+}
+
+#[test]
+fn tsv_complement_f2() -> TestResult {
+    run(
+        &["--complement", "-f", "2", TSV],
+        "tests/expected/books.tsv.complement.f2.out",
+    )
+}
+
+#[test]
+fn tsv_b1_4() -> TestResult {
+    run(&["-b", "1-4", TSV], "tests/expected/books.tsv.b1-4.out")
+}
+
+#[test]
+fn tsv_b1_3_4_output_delimiter() -> TestResult {
+    run(
+        &["-b", "1,3-4", "--output-delimiter=:", TSV],
+        "tests/expected/books.tsv.b1,3-4.od.out",
+    )
+}
+
+#[test]
+fn tsv_b_adjacent_ranges() -> TestResult {
+    run(
+        &["-b", "1-2,3", "--output-delimiter=:", TSV],
+        "tests/expected/books.tsv.b1-2,3.od.out",
+    )
+}
+
+#[test]
+fn tsv_complement_b8_to_end() -> TestResult {
+    run(
+        &["--complement", "-b", "8-", TSV],
+        "tests/expected/books.tsv.complement.b8-.out",
+    )
+}
+
+#[test]
+fn tsv_c1_4() -> TestResult {
+    run(&["-c", "1-4", TSV], "tests/expected/books.tsv.c1-4.out")
+}
+
+#[test]
+fn tsv_c_to_4_stdin() -> TestResult {
+    run_stdin(&["-c", "-4"], TSV, "tests/expected/books.tsv.c1-4.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn mixed_f2() -> TestResult {
+    run(
+        &["-d", ":", "-f", "2", MIXED],
+        "tests/expected/mixed.txt.f2.out",
+    )
+}
+
+#[test]
+fn mixed_only_delimited_f2() -> TestResult {
+    run(
+        &["-s", "-d", ":", "-f", "2", MIXED],
+        "tests/expected/mixed.txt.s.f2.out",
+    )
+}
+
+#[test]
+fn mixed_f5() -> TestResult {
+    run(&["-d:", "-f5", MIXED], "tests/expected/mixed.txt.f5.out")
+}
+
+#[test]
+fn mixed_complement_f1() -> TestResult {
+    run(
+        &["--complement", "-d", ":", "-f", "1", MIXED],
+        "tests/expected/mixed.txt.complement.f1.out",
+    )
+}
+
+#[test]
+fn mixed_f1_to_end_output_delimiter() -> TestResult {
+    run(
+        &["-d", ":", "-f", "1-", "--output-delimiter", ",", MIXED],
+        "tests/expected/mixed.txt.f1-.od.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn csv_plain_f3() -> TestResult {
+    run(
+        &["-d", ",", "-f", "3", CSV],
+        "tests/expected/books.csv.f3.out",
+    )
+}
+
+#[test]
+fn csv_f3() -> TestResult {
+    run(
+        &["--csv", "-f", "3", CSV],
+        "tests/expected/books.csv.csv.f3.out",
+    )
+}
+
+#[test]
+fn csv_f3_stdin() -> TestResult {
+    run_stdin(
+        &["--csv", "-d", ",", "-f", "3"],
+        CSV,
+        "tests/expected/books.csv.csv.f3.out",
+    )
+}
+
+#[test]
+fn csv_f2_to_end() -> TestResult {
+    run(
+        &["--csv", "-f", "2-", CSV],
+        "tests/expected/books.csv.csv.f2-.out",
+    )
+}
+
+#[test]
+fn csv_f1_3_output_delimiter() -> TestResult {
+    run(
+        &["--csv", "-f", "1,3", "--output-delimiter", ";", CSV],
+        "tests/expected/books.csv.csv.f1,3.od.out",
+    )
+}
+
+#[test]
+fn csv_multiline_fields() -> TestResult {
+    run(
+        &["--csv", "-f", "2", NOTES],
+        "tests/expected/notes.csv.csv.f2.out",
+    )
+}
+
+#[test]
+fn csv_unterminated_quote() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--csv", "-f", "2"])
+        .write_stdin("a,b\n1,\"open\n2,x\n")
+        .assert()
+        .success()
+        .stdout("b\n\"open\n2,x\"\n")
+        .stderr("-: unterminated quoted field\n");
+
+    Ok(())
+}
@@ -0,0 +1,417 @@
+use clap::{Arg, Command};
+use common::{flag, Error, MyResult};
+use std::ffi::OsString;
+use std::io::Write;
+
+mod printf;
+
+#[derive(Debug)]
+pub struct Config {
+    /// The arguments to print, separated by single spaces.
+    pub text: Vec<String>,
+    /// Leave off the trailing newline (-n).
+    pub omit_newline: bool,
+    /// Expand backslash escapes (-e).
+    pub escapes: bool,
+    /// Format `text` with this printf format instead (--printf).
+    pub printf: Option<String>,
+}
+
+/// Writes the text described by `config` to `out`.
+pub fn run(config: Config, out: &mut impl Write) -> MyResult<()> {
+    if let Some(format) = &config.printf {
+        return printf::printf(format, &config.text, out);
+    }
+
+    let joined_text = config.text.join(" ");
+
+    if config.escapes {
+        let (bytes, stop) = expand_escapes(&joined_text);
+        out.write_all(&bytes)?;
+        if stop {
+            return Ok(());
+        }
+    } else {
+        out.write_all(joined_text.as_bytes())?;
+    }
+
+    if !config.omit_newline {
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Which echo the command line is parsed like. `ECHOR_COMPAT` picks one of
+/// `gnu`, `posix` or `bsd`; otherwise `POSIXLY_CORRECT` selects POSIX, and
+/// with neither set arguments go through clap as usual.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compat {
+    /// Options anywhere, `--` ends them and unknown options are errors.
+    Native,
+    /// Leading arguments made only of `-n`, `-e` and `-E` are options;
+    /// everything from the first other argument on is text.
+    Gnu,
+    /// Escapes are always expanded, and options are only read, as for
+    /// `Gnu`, when the first argument is exactly `-n`.
+    Posix,
+    /// Only a first argument of exactly `-n` is an option, escapes are not
+    /// expanded and a `\c` ending the last argument drops the newline.
+    Bsd,
+}
+
+impl Compat {
+    /// The mode selected by the environment.
+    pub fn from_env() -> MyResult<Compat> {
+        match std::env::var_os("ECHOR_COMPAT") {
+            Some(mode) => match mode.to_str() {
+                Some("gnu") => Ok(Compat::Gnu),
+                Some("posix") => Ok(Compat::Posix),
+                Some("bsd") => Ok(Compat::Bsd),
+                _ => Err(Error::Usage(format!(
+                    "invalid ECHOR_COMPAT -- {}",
+                    mode.to_string_lossy()
+                ))),
+            },
+            None if std::env::var_os("POSIXLY_CORRECT").is_some() => Ok(Compat::Posix),
+            None => Ok(Compat::Native),
+        }
+    }
+}
+
+pub fn get_args() -> MyResult<Config> {
+    get_args_from(std::env::args_os())
+}
+
+/// Like `get_args`, but parses `args` (whose first item is the program name)
+/// instead of the process arguments.
+pub fn get_args_from<I, T>(args: I) -> MyResult<Config>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    get_args_compat(Compat::from_env()?, args)
+}
+
+/// Like `get_args_from`, but parses `args` like the echo chosen by `compat`
+/// rather than the one the environment asks for.
+pub fn get_args_compat<I, T>(compat: Compat, args: I) -> MyResult<Config>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+    // GNU echo only answers --help and --version when given nothing else
+    let help_or_version = args.len() == 2 && (args[1] == "--help" || args[1] == "--version");
+    match compat {
+        Compat::Native => {}
+        Compat::Gnu if help_or_version => {}
+        _ => return Ok(parse_compat(compat, &args[1.min(args.len())..])),
+    }
+
+    let mut matches = Command::new("echor")
+        .version("0.1.0")
+        .author("Derek Warner <derekw3@illinois.edu>")
+        .about("Rust echo implementation")
+        .arg(
+            Arg::new("text")
+                .help("Input text")
+                .required_unless_present("printf")
+                .num_args(1..),
+        )
+        .arg(
+            Arg::new("omit_newline")
+                .short('n')
+                .help("Do not print newline")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("escapes")
+                .short('e')
+                .help("Enable interpretation of backslash escapes")
+                .num_args(0)
+                .overrides_with("no_escapes"),
+        )
+        .arg(
+            Arg::new("no_escapes")
+                .short('E')
+                .help("Disable interpretation of backslash escapes (default)")
+                .num_args(0)
+                .overrides_with("escapes"),
+        )
+        .arg(
+            Arg::new("printf")
+                .long("printf")
+                .value_name("FORMAT")
+                .help("Format the text arguments like printf(1)")
+                .num_args(1)
+                .allow_hyphen_values(true)
+                .conflicts_with_all(["omit_newline", "escapes", "no_escapes"]),
+        )
+        .allow_negative_numbers(true)
+        .get_matches_from(args);
+
+    Ok(Config {
+        text: matches
+            .remove_many("text")
+            .map(|text| text.collect())
+            .unwrap_or_default(),
+        omit_newline: flag(&matches, "omit_newline"),
+        escapes: flag(&matches, "escapes"),
+        printf: matches.remove_one("printf"),
+    })
+}
+
+/// Splits `args` (without the program name) into options and text the way
+/// the echo of `compat` does. None of them ever reject an argument.
+fn parse_compat(compat: Compat, args: &[OsString]) -> Config {
+    let mut text: Vec<String> = args
+        .iter()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    let mut config = Config {
+        text: vec![],
+        omit_newline: false,
+        escapes: compat == Compat::Posix,
+        printf: None,
+    };
+
+    match compat {
+        Compat::Gnu => gnu_options(&mut text, &mut config),
+        // As in GNU echo with POSIXLY_CORRECT, a first -n lets the option
+        // words be read; escapes stay on whatever they say
+        Compat::Posix => {
+            if text.first().map(String::as_str) == Some("-n") {
+                gnu_options(&mut text, &mut config);
+                config.escapes = true;
+            }
+        }
+        _ => {
+            if text.first().map(String::as_str) == Some("-n") {
+                text.remove(0);
+                config.omit_newline = true;
+            }
+        }
+    }
+
+    if compat == Compat::Bsd {
+        if let Some(last) = text.last_mut() {
+            if let Some(stripped) = last.strip_suffix("\\c") {
+                *last = stripped.to_string();
+                config.omit_newline = true;
+            }
+        }
+    }
+
+    config.text = text;
+    config
+}
+
+/// Removes the leading arguments made only of `-n`, `-e` and `-E` from
+/// `text`, applying them to `config`.
+fn gnu_options(text: &mut Vec<String>, config: &mut Config) {
+    let options = text
+        .iter()
+        .take_while(|arg| {
+            arg.len() > 1
+                && arg.starts_with('-')
+                && arg[1..].chars().all(|c| matches!(c, 'n' | 'e' | 'E'))
+        })
+        .count();
+    for option in text.drain(..options) {
+        for c in option[1..].chars() {
+            match c {
+                'n' => config.omit_newline = true,
+                'e' => config.escapes = true,
+                _ => config.escapes = false,
+            }
+        }
+    }
+}
+
+/// Expands backslash escapes the way GNU `echo -e` does. The flag is true
+/// when `\c` cut the output short, which also drops the trailing newline.
+/// Unknown escapes are printed as they are.
+pub fn expand_escapes(text: &str) -> (Vec<u8>, bool) {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        i += 1;
+        if byte != b'\\' || i == bytes.len() {
+            out.push(byte);
+        } else if expand_escape(bytes, &mut i, Octal::Echo, &mut out) {
+            return (out, true);
+        }
+    }
+    (out, false)
+}
+
+/// Where the digits of an octal escape start.
+#[derive(Clone, Copy, PartialEq)]
+enum Octal {
+    /// echo: `\0NNN`, or `\NNN` when the first digit is not a zero.
+    Echo,
+    /// printf: `\NNN`, with a leading zero counting as one of the digits.
+    Printf,
+}
+
+/// Appends the escape whose letter is at `bytes[*i]` to `out`, moving `i`
+/// past it. Returns true for `\c`, after which nothing more is printed.
+fn expand_escape(bytes: &[u8], i: &mut usize, octal: Octal, out: &mut Vec<u8>) -> bool {
+    let escape = bytes[*i];
+    *i += 1;
+    match escape {
+        b'\\' => out.push(b'\\'),
+        b'a' => out.push(0x07),
+        b'b' => out.push(0x08),
+        b'c' => return true,
+        b'e' => out.push(0x1b),
+        b'f' => out.push(0x0c),
+        b'n' => out.push(b'\n'),
+        b'r' => out.push(b'\r'),
+        b't' => out.push(b'\t'),
+        b'v' => out.push(0x0b),
+        b'x' => match take_digits(bytes, i, 16, 2) {
+            Some(value) => out.push(value as u8),
+            None => out.extend(b"\\x"),
+        },
+        // \0NNN: up to three octal digits after the zero
+        b'0' if octal == Octal::Echo => out.push(take_digits(bytes, i, 8, 3).unwrap_or(0) as u8),
+        // \NNN: the escape itself is the first of up to three digits
+        b'0'..=b'7' => {
+            *i -= 1;
+            out.push(take_digits(bytes, i, 8, 3).unwrap_or(0) as u8);
+        }
+        _ => {
+            out.push(b'\\');
+            out.push(escape);
+        }
+    }
+    false
+}
+
+/// Reads up to `max` digits in `radix` starting at `bytes[*i]`, advancing
+/// `i` past them. Values too big for a byte wrap, as they do in C.
+fn take_digits(bytes: &[u8], i: &mut usize, radix: u32, max: usize) -> Option<u32> {
+    let mut value = None;
+    for _ in 0..max {
+        match bytes.get(*i).and_then(|&b| (b as char).to_digit(radix)) {
+            Some(digit) => {
+                value = Some(value.unwrap_or(0) * radix + digit);
+                *i += 1;
+            }
+            None => break,
+        }
+    }
+    value
+}
+
+#[test]
+fn test_expand_escapes() {
+    assert_eq!(expand_escapes("a\\tb\\n"), (b"a\tb\n".to_vec(), false));
+    assert_eq!(
+        expand_escapes("\\\\ \\a\\b\\e\\f\\r\\v"),
+        (b"\\ \x07\x08\x1b\x0c\r\x0b".to_vec(), false)
+    );
+    assert_eq!(expand_escapes("stop\\chere"), (b"stop".to_vec(), true));
+    assert_eq!(
+        expand_escapes("\\x41\\x4\\xg"),
+        (b"A\x04\\xg".to_vec(), false)
+    );
+    assert_eq!(
+        expand_escapes("\\0101\\101\\0\\08"),
+        (b"AA\x00\x008".to_vec(), false)
+    );
+    assert_eq!(
+        expand_escapes("\\0777\\q trailing\\"),
+        (b"\xff\\q trailing\\".to_vec(), false)
+    );
+}
+
+#[test]
+fn test_run() {
+    let echo = |text: &[&str], omit_newline, escapes| {
+        let config = Config {
+            text: text.iter().map(|s| s.to_string()).collect(),
+            omit_newline,
+            escapes,
+            printf: None,
+        };
+        let mut out = Vec::new();
+        run(config, &mut out).unwrap();
+        out
+    };
+
+    assert_eq!(echo(&["Hello", "there"], false, false), b"Hello there\n");
+    assert_eq!(echo(&["Hello  there"], true, false), b"Hello  there");
+    assert_eq!(echo(&["a\\tb"], false, false), b"a\\tb\n");
+    assert_eq!(echo(&["a\\tb"], false, true), b"a\tb\n");
+    assert_eq!(echo(&["a\\cb", "c"], false, true), b"a");
+}
+
+#[test]
+fn test_get_args_compat() {
+    let parse = |compat, args: &[&str]| {
+        let config = get_args_compat(compat, ["echor"].iter().chain(args)).unwrap();
+        (config.text, config.omit_newline, config.escapes)
+    };
+    let text = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    assert_eq!(
+        parse(Compat::Native, &["a", "-n"]),
+        (text(&["a"]), true, false)
+    );
+    assert_eq!(
+        parse(Compat::Native, &["--", "-n"]),
+        (text(&["-n"]), false, false)
+    );
+
+    assert_eq!(parse(Compat::Gnu, &[]), (text(&[]), false, false));
+    assert_eq!(
+        parse(Compat::Gnu, &["-ne", "-E", "-x", "-n"]),
+        (text(&["-x", "-n"]), true, false)
+    );
+    assert_eq!(
+        parse(Compat::Gnu, &["--", "-n"]),
+        (text(&["--", "-n"]), false, false)
+    );
+    assert_eq!(
+        parse(Compat::Gnu, &["-", "a"]),
+        (text(&["-", "a"]), false, false)
+    );
+    assert_eq!(
+        parse(Compat::Gnu, &["a", "--help"]),
+        (text(&["a", "--help"]), false, false)
+    );
+
+    assert_eq!(
+        parse(Compat::Posix, &["-n", "-e", "a"]),
+        (text(&["a"]), true, true)
+    );
+    assert_eq!(
+        parse(Compat::Posix, &["-n", "-E", "a", "-n"]),
+        (text(&["a", "-n"]), true, true)
+    );
+    assert_eq!(
+        parse(Compat::Posix, &["-ne", "a"]),
+        (text(&["-ne", "a"]), false, true)
+    );
+    assert_eq!(
+        parse(Compat::Posix, &["--help"]),
+        (text(&["--help"]), false, true)
+    );
+
+    assert_eq!(
+        parse(Compat::Bsd, &["-n", "-n"]),
+        (text(&["-n"]), true, false)
+    );
+    assert_eq!(
+        parse(Compat::Bsd, &["-e", "a\\c"]),
+        (text(&["-e", "a"]), true, false)
+    );
+    assert_eq!(
+        parse(Compat::Bsd, &["a\\c", "b"]),
+        (text(&["a\\c", "b"]), false, false)
+    );
+}
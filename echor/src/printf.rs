@@ -0,0 +1,636 @@
+//! `echor --printf FORMAT [ARGUMENT]...`, formatting the arguments the way
+//! GNU printf does. The format is reused until every argument is consumed;
+//! missing arguments count as empty strings and zeros.
+
+use crate::{expand_escape, expand_escapes, Octal};
+use common::{Error, MyResult};
+use std::io::Write;
+
+/// Writes `args` to `out` as described by `format`. Arguments that are not
+/// valid numbers print as far as they could be read and the first such
+/// problem is returned once everything has been written.
+pub fn printf(format: &str, args: &[String], out: &mut impl Write) -> MyResult<()> {
+    let mut printer = Printer {
+        args,
+        next: 0,
+        out: Vec::new(),
+        error: None,
+    };
+
+    let result = loop {
+        match printer.format_once(format.as_bytes()) {
+            Ok(false) => {}
+            stopped => break stopped.map(|_| ()),
+        }
+        if printer.next == 0 {
+            if let Some(arg) = args.first() {
+                eprintln!(
+                    "warning: ignoring excess arguments, starting with '{}'",
+                    arg
+                );
+            }
+            break Ok(());
+        }
+        if printer.next >= args.len() {
+            break Ok(());
+        }
+    };
+
+    out.write_all(&printer.out)?;
+    result?;
+    printer.error.map_or(Ok(()), Err)
+}
+
+struct Printer<'a> {
+    args: &'a [String],
+    next: usize,
+    out: Vec<u8>,
+    /// The first argument that was not a valid number.
+    error: Option<Error>,
+}
+
+/// A parsed conversion specification such as `%-08.3f`.
+#[derive(Default)]
+struct Spec {
+    left: bool,
+    plus: bool,
+    space: bool,
+    alternate: bool,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+impl<'a> Printer<'a> {
+    fn next_arg(&mut self) -> Option<&'a str> {
+        let arg = self.args.get(self.next)?;
+        self.next += 1;
+        Some(arg)
+    }
+
+    fn note(&mut self, arg: &str, problem: &str) {
+        if self.error.is_none() {
+            self.error = Some(Error::Usage(format!("'{}': {}", arg, problem)));
+        }
+    }
+
+    /// Formats one pass over `format`. Returns true when `\c` ended the
+    /// output.
+    fn format_once(&mut self, format: &[u8]) -> MyResult<bool> {
+        let mut i = 0;
+        while i < format.len() {
+            let byte = format[i];
+            i += 1;
+            match byte {
+                b'\\' if i < format.len() => {
+                    if expand_escape(format, &mut i, Octal::Printf, &mut self.out) {
+                        return Ok(true);
+                    }
+                }
+                b'%' if format.get(i) == Some(&b'%') => {
+                    self.out.push(b'%');
+                    i += 1;
+                }
+                b'%' => {
+                    if self.conversion(format, &mut i)? {
+                        return Ok(true);
+                    }
+                }
+                _ => self.out.push(byte),
+            }
+        }
+        Ok(false)
+    }
+
+    /// Formats the conversion starting just after the `%` at `format[*i]`.
+    /// Returns true when a `%b` argument ended the output with `\c`.
+    fn conversion(&mut self, format: &[u8], i: &mut usize) -> MyResult<bool> {
+        let start = *i - 1;
+        let mut spec = Spec::default();
+        let mut flags = false;
+        while let Some(&flag) = format.get(*i) {
+            match flag {
+                b'-' => spec.left = true,
+                b'+' => spec.plus = true,
+                b' ' => spec.space = true,
+                b'#' => spec.alternate = true,
+                b'0' => spec.zero = true,
+                b'\'' => {}
+                _ => break,
+            }
+            flags = true;
+            *i += 1;
+        }
+
+        let has_width = matches!(format.get(*i), Some(b'*' | b'0'..=b'9'));
+        if let Some(width) = self.number(format, i, "invalid field width")? {
+            if width < 0 {
+                spec.left = true;
+            }
+            spec.width = width.unsigned_abs() as usize;
+        }
+        let has_precision = format.get(*i) == Some(&b'.');
+        if has_precision {
+            *i += 1;
+            let precision = self.number(format, i, "invalid precision")?.unwrap_or(0);
+            spec.precision = usize::try_from(precision).ok();
+        }
+        while matches!(
+            format.get(*i),
+            Some(b'h' | b'l' | b'L' | b'j' | b'z' | b't')
+        ) {
+            *i += 1;
+        }
+
+        let conversion = format.get(*i).copied();
+        *i += 1;
+        let end = (*i).min(format.len());
+        let invalid = || {
+            Error::Usage(format!(
+                "{}: invalid conversion specification",
+                String::from_utf8_lossy(&format[start..end])
+            ))
+        };
+        // GNU printf refuses the flags that mean nothing for a conversion
+        let valid = match conversion {
+            Some(b'b' | b'q') => !flags && !has_width && !has_precision,
+            Some(b'c') => !spec.zero && !spec.alternate && !has_precision,
+            Some(b's') => !spec.zero && !spec.alternate,
+            Some(b'd' | b'i') => !spec.alternate,
+            Some(b'u') => !spec.alternate,
+            Some(b'o' | b'x' | b'X') => true,
+            Some(b'f' | b'F' | b'e' | b'E' | b'g' | b'G') => true,
+            _ => false,
+        };
+        let conversion = match conversion {
+            Some(conversion) if valid => conversion,
+            _ => return Err(invalid()),
+        };
+
+        match conversion {
+            b's' => {
+                let arg = self.next_arg().unwrap_or_default();
+                let text = match spec.precision {
+                    Some(precision) => arg.chars().take(precision).collect(),
+                    None => arg.to_string(),
+                };
+                self.pad(&spec, "", text.as_bytes(), false);
+            }
+            b'b' => {
+                let arg = self.next_arg().unwrap_or_default();
+                let (bytes, stop) = expand_escapes(arg);
+                self.out.extend(bytes);
+                return Ok(stop);
+            }
+            b'q' => {
+                let arg = self.next_arg().unwrap_or_default();
+                self.out.extend(shell_quote(arg).as_bytes());
+            }
+            b'c' => {
+                let byte = self
+                    .next_arg()
+                    .map_or(0, |arg| arg.bytes().next().unwrap_or(0));
+                self.pad(&spec, "", &[byte], false);
+            }
+            b'd' | b'i' => {
+                let value = self.integer(true);
+                let sign = if value < 0 {
+                    "-"
+                } else if spec.plus {
+                    "+"
+                } else if spec.space {
+                    " "
+                } else {
+                    ""
+                };
+                self.pad_integer(&spec, sign, value.unsigned_abs().to_string());
+            }
+            b'u' | b'o' | b'x' | b'X' => {
+                let value = self.integer(false) as u64;
+                let (digits, prefix) = match conversion {
+                    b'o' => (format!("{:o}", value), "0"),
+                    b'x' => (format!("{:x}", value), "0x"),
+                    b'X' => (format!("{:X}", value), "0X"),
+                    _ => (value.to_string(), ""),
+                };
+                // `%#o` only adds a zero when the digits do not start with one
+                let zero_first = match spec.precision {
+                    Some(precision) => precision > digits.len() || (value == 0 && precision > 0),
+                    None => value == 0,
+                };
+                let prefix = match conversion {
+                    b'o' if spec.alternate && !zero_first => prefix,
+                    b'x' | b'X' if spec.alternate && value != 0 => prefix,
+                    _ => "",
+                };
+                self.pad_integer(&spec, prefix, digits);
+            }
+            _ => {
+                let value = self.float();
+                let sign = if value.is_sign_negative() && !value.is_nan() {
+                    "-"
+                } else if spec.plus {
+                    "+"
+                } else if spec.space {
+                    " "
+                } else {
+                    ""
+                };
+                let body = format_float(conversion, value.abs(), &spec);
+                self.pad(&spec, sign, body.as_bytes(), value.is_finite());
+            }
+        }
+        Ok(false)
+    }
+
+    /// Reads a width or precision: digits, or `*` to take it from the next
+    /// argument. Like GNU printf, values outside the range of an `int` are
+    /// refused with `problem`.
+    fn number(&mut self, format: &[u8], i: &mut usize, problem: &str) -> MyResult<Option<i64>> {
+        let (number, text) = if format.get(*i) == Some(&b'*') {
+            *i += 1;
+            let arg = self.args.get(self.next).map_or("", String::as_str);
+            (self.integer(true) as i64, arg)
+        } else {
+            let digits = format[*i..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit())
+                .count();
+            if digits == 0 {
+                return Ok(None);
+            }
+            let text = std::str::from_utf8(&format[*i..*i + digits]).unwrap();
+            *i += digits;
+            (text.parse().unwrap_or(i64::MAX), text)
+        };
+        if i32::try_from(number).is_err() {
+            return Err(Error::Usage(format!("{}: '{}'", problem, text)));
+        }
+        Ok(Some(number))
+    }
+
+    /// Takes the next argument as an integer, like strtoimax for `signed`
+    /// and strtoumax otherwise. Unsigned values come back in the low 64 bits.
+    fn integer(&mut self, signed: bool) -> i128 {
+        let Some(arg) = self.next_arg() else {
+            return 0;
+        };
+        let (negative, magnitude, rest) = match parse_integer(arg) {
+            Some(parsed) => parsed,
+            None => {
+                if !arg.is_empty() {
+                    self.note(arg, "expected a numeric value");
+                }
+                return 0;
+            }
+        };
+        if !rest.is_empty() {
+            self.note(arg, "value not completely converted");
+        }
+
+        let (min, max) = if signed {
+            (i64::MIN as i128, i64::MAX as i128)
+        } else {
+            (-(u64::MAX as i128), u64::MAX as i128)
+        };
+        let value = match magnitude {
+            Some(magnitude) if negative => -(magnitude as i128),
+            Some(magnitude) => magnitude as i128,
+            None if negative => i128::MIN,
+            None => i128::MAX,
+        };
+        let value = if value < min || value > max {
+            self.note(arg, "Numerical result out of range");
+            if signed && negative {
+                min
+            } else {
+                max
+            }
+        } else {
+            value
+        };
+
+        if signed || value >= 0 {
+            value
+        } else {
+            (value as i64) as u64 as i128
+        }
+    }
+
+    /// Takes the next argument as a floating point number.
+    fn float(&mut self) -> f64 {
+        let Some(arg) = self.next_arg() else {
+            return 0.0;
+        };
+        let trimmed = arg.trim_start();
+        if let Some(code) = character_code(trimmed) {
+            return code as f64;
+        }
+        if let Ok(value) = trimmed.parse() {
+            return value;
+        }
+
+        // Print as much of the number as there is, like strtod
+        let prefix = (1..trimmed.len())
+            .rev()
+            .filter(|&end| trimmed.is_char_boundary(end))
+            .find_map(|end| trimmed[..end].parse().ok());
+        match prefix {
+            Some(value) => {
+                self.note(arg, "value not completely converted");
+                value
+            }
+            None => {
+                if !arg.is_empty() {
+                    self.note(arg, "expected a numeric value");
+                }
+                0.0
+            }
+        }
+    }
+
+    /// Writes integer `digits` after `prefix`, zero filled to the precision.
+    fn pad_integer(&mut self, spec: &Spec, prefix: &str, mut digits: String) {
+        match spec.precision {
+            Some(0) if digits == "0" => digits.clear(),
+            Some(precision) if digits.len() < precision => {
+                digits.insert_str(0, &"0".repeat(precision - digits.len()));
+            }
+            _ => {}
+        }
+        self.pad(spec, prefix, digits.as_bytes(), spec.precision.is_none());
+    }
+
+    /// Writes `prefix` and `body` padded to the width: on the right for `-`,
+    /// with zeros between the two for `0` when `zero_fill` allows it, and
+    /// otherwise with spaces on the left.
+    fn pad(&mut self, spec: &Spec, prefix: &str, body: &[u8], zero_fill: bool) {
+        let fill = spec.width.saturating_sub(prefix.len() + body.len());
+        if spec.left {
+            self.out.extend(prefix.as_bytes());
+            self.out.extend(body);
+            self.out.extend(std::iter::repeat_n(b' ', fill));
+        } else if spec.zero && zero_fill {
+            self.out.extend(prefix.as_bytes());
+            self.out.extend(std::iter::repeat_n(b'0', fill));
+            self.out.extend(body);
+        } else {
+            self.out.extend(std::iter::repeat_n(b' ', fill));
+            self.out.extend(prefix.as_bytes());
+            self.out.extend(body);
+        }
+    }
+}
+
+/// Splits an integer argument into its sign, its magnitude (None when it
+/// does not fit in 64 bits) and whatever follows the digits. Accepts
+/// leading blanks, `0x` hex, `0` octal and `'c` for the code of `c`.
+fn parse_integer(arg: &str) -> Option<(bool, Option<u64>, &str)> {
+    let trimmed = arg.trim_start();
+    if let Some(code) = character_code(trimmed) {
+        return Some((false, Some(code.into()), ""));
+    }
+
+    let (negative, unsigned) = match trimmed.as_bytes().first() {
+        Some(b'-') => (true, &trimmed[1..]),
+        Some(b'+') => (false, &trimmed[1..]),
+        _ => (false, trimmed),
+    };
+    let (radix, digits) = match unsigned.strip_prefix("0x").or(unsigned.strip_prefix("0X")) {
+        Some(hex) if hex.starts_with(|c: char| c.is_ascii_hexdigit()) => (16, hex),
+        _ if unsigned.starts_with('0') => (8, unsigned),
+        _ => (10, unsigned),
+    };
+    let end = digits
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(digits.len());
+    if end == 0 {
+        return None;
+    }
+    let magnitude = u64::from_str_radix(&digits[..end], radix).ok();
+    Some((negative, magnitude, &digits[end..]))
+}
+
+/// The character code given by a leading `'` or `"`, as in `'A` for 65.
+fn character_code(arg: &str) -> Option<u32> {
+    let rest = arg.strip_prefix(['\'', '"'])?;
+    rest.chars().next().map(u32::from)
+}
+
+/// Formats the magnitude of a floating point `value` for `%f`, `%e` or
+/// `%g` (or their upper case forms).
+fn format_float(conversion: u8, value: f64, spec: &Spec) -> String {
+    let upper = conversion.is_ascii_uppercase();
+    let body = if value.is_nan() {
+        "nan".to_string()
+    } else if value.is_infinite() {
+        "inf".to_string()
+    } else {
+        let precision = spec.precision.unwrap_or(6);
+        match conversion.to_ascii_lowercase() {
+            b'f' => {
+                let mut fixed = format!("{:.*}", precision, value);
+                if spec.alternate && precision == 0 {
+                    fixed.push('.');
+                }
+                fixed
+            }
+            b'e' => exponential(value, precision, spec.alternate),
+            _ => general(value, precision, spec.alternate),
+        }
+    };
+    if upper {
+        body.to_ascii_uppercase()
+    } else {
+        body
+    }
+}
+
+/// `%e`: one digit before the point and a signed exponent of at least two
+/// digits, e.g. `3.141590e+00`.
+fn exponential(value: f64, precision: usize, alternate: bool) -> String {
+    let formatted = format!("{:.*e}", precision, value);
+    let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    let point = if alternate && precision == 0 { "." } else { "" };
+    let sign = if exponent < 0 { '-' } else { '+' };
+    format!("{}{}e{}{:02}", mantissa, point, sign, exponent.abs())
+}
+
+/// `%g`: `%e` for very small or large values and `%f` otherwise, with
+/// `precision` significant digits and trailing zeros removed unless
+/// `alternate`.
+fn general(value: f64, precision: usize, alternate: bool) -> String {
+    let precision = precision.max(1);
+    let rounded = format!("{:.*e}", precision - 1, value);
+    let exponent: i64 = rounded
+        .split_once('e')
+        .and_then(|(_, exponent)| exponent.parse().ok())
+        .unwrap_or(0);
+
+    let formatted = if exponent < -4 || exponent >= precision as i64 {
+        exponential(value, precision - 1, alternate)
+    } else {
+        let decimals = (precision as i64 - 1 - exponent) as usize;
+        let mut fixed = format!("{:.*}", decimals, value);
+        if alternate && decimals == 0 {
+            fixed.push('.');
+        }
+        fixed
+    };
+    if alternate {
+        return formatted;
+    }
+
+    let (number, exponent) = match formatted.find('e') {
+        Some(e) => formatted.split_at(e),
+        None => (formatted.as_str(), ""),
+    };
+    let number = if number.contains('.') {
+        number.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        number
+    };
+    format!("{}{}", number, exponent)
+}
+
+/// Quotes `arg` so a POSIX shell reads it back unchanged, as `%q` does in
+/// GNU printf: plain words as they are, single quotes around anything
+/// special and `$'...'` for control characters.
+fn shell_quote(arg: &str) -> String {
+    const SPECIAL: &[char] = &[
+        ' ', '!', '"', '$', '&', '\'', '(', ')', '*', ';', '<', '=', '>', '?', '[', '\\', '^', '`',
+        '|',
+    ];
+    let control = arg.chars().any(char::is_control);
+    let plain =
+        !control && !arg.is_empty() && !arg.starts_with(['#', '~']) && !arg.contains(SPECIAL);
+    if plain {
+        return arg.to_string();
+    }
+    if !control && arg.contains('\'') && !arg.contains(['$', '`', '"', '\\', '!']) {
+        return format!("\"{}\"", arg);
+    }
+
+    let mut quoted = String::from("'");
+    let mut in_dollar = false;
+    for c in arg.chars() {
+        if c.is_control() {
+            if !in_dollar {
+                quoted.push_str("'$'");
+                in_dollar = true;
+            }
+            match c {
+                '\x07' => quoted.push_str("\\a"),
+                '\x08' => quoted.push_str("\\b"),
+                '\t' => quoted.push_str("\\t"),
+                '\n' => quoted.push_str("\\n"),
+                '\x0b' => quoted.push_str("\\v"),
+                '\x0c' => quoted.push_str("\\f"),
+                '\r' => quoted.push_str("\\r"),
+                _ => {
+                    let mut buffer = [0; 4];
+                    for byte in c.encode_utf8(&mut buffer).bytes() {
+                        quoted.push_str(&format!("\\{:03o}", byte));
+                    }
+                }
+            }
+        } else {
+            if in_dollar {
+                quoted.push_str("''");
+                in_dollar = false;
+            }
+            match c {
+                '\'' => quoted.push_str("'\\''"),
+                _ => quoted.push(c),
+            }
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+#[test]
+fn test_printf() {
+    let format = |format: &str, args: &[&str]| {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut out = Vec::new();
+        let result = printf(format, &args, &mut out);
+        (String::from_utf8_lossy(&out).into_owned(), result.is_ok())
+    };
+    let ok = |text: &str| (text.to_string(), true);
+
+    assert_eq!(format("%s-%d;", &["a", "1", "b"]), ok("a-1;b-0;"));
+    assert_eq!(format("%s %s\\n", &["a"]), ok("a \n"));
+    assert_eq!(
+        format("%5s|%-5s|%.2s", &["ab", "ab", "abc"]),
+        ok("   ab|ab   |ab")
+    );
+    assert_eq!(
+        format(
+            "%+d|% d|%05d|%-5d|%.3d|%5.3d|%.0d|%#x|%#o|%#X",
+            &["5", "5", "-42", "7", "7", "7", "0", "255", "8", "255"]
+        ),
+        ok("+5| 5|-0042|7    |007|  007||0xff|010|0XFF")
+    );
+    assert_eq!(
+        format("%u %x %o", &["-1", "-1", "-1"]),
+        ok("18446744073709551615 ffffffffffffffff 1777777777777777777777")
+    );
+    assert_eq!(format("%d %d %i", &["0x1f", "010", "'A"]), ok("31 8 65"));
+    assert_eq!(
+        format(
+            "%f|%e|%g|%G|%E",
+            &["3.14159", "31415.9", "0.0001", "1e-5", "0"]
+        ),
+        ok("3.141590|3.141590e+04|0.0001|1E-05|0.000000E+00")
+    );
+    assert_eq!(
+        format(
+            "%g|%g|%#g|%.0e|%#.0f|%.3g|%010.2g",
+            &["100000", "1e6", "1", "12345", "3", "0.0001234", "1.5"]
+        ),
+        ok("100000|1e+06|1.00000|1e+04|3.|0.000123|00000001.5")
+    );
+    assert_eq!(format("%05f|%f", &["inf", "-nan"]), ok("  inf|nan"));
+    assert_eq!(
+        format("%*d|%-*d|%.*f", &["5", "1", "4", "2", "2", "3.14159"]),
+        ok("    1|2   |3.14")
+    );
+    assert_eq!(format("%c%c|%5c", &["abc", "", "a"]), ok("a\0|    a"));
+    assert_eq!(
+        format("%b|%s", &["a\\tb\\0101", "a\\tb"]),
+        ok("a\tbA|a\\tb")
+    );
+    assert_eq!(format("a\\101\\x41%b%s", &["b\\cnot", "x"]), ok("aAAb"));
+    assert_eq!(format("%i%%\\c%d", &["5"]), ok("5%"));
+    assert_eq!(format("%q %q", &["a b", "x"]), ok("'a b' x"));
+
+    assert_eq!(
+        format("%d|%d", &["12abc", "x"]),
+        ("12|0".to_string(), false)
+    );
+    assert_eq!(
+        format("%d", &["9223372036854775808"]),
+        ("9223372036854775807".to_string(), false)
+    );
+    assert_eq!(format("a%z", &[]), ("a".to_string(), false));
+    assert_eq!(format("%05s", &["a"]), (String::new(), false));
+    assert_eq!(format("%5b", &["a"]), (String::new(), false));
+    assert_eq!(format("%", &[]), (String::new(), false));
+}
+
+#[test]
+fn test_shell_quote() {
+    assert_eq!(shell_quote(""), "''");
+    assert_eq!(shell_quote("ab_@%+:,./-x"), "ab_@%+:,./-x");
+    assert_eq!(shell_quote("a#~"), "a#~");
+    assert_eq!(shell_quote("#a"), "'#a'");
+    assert_eq!(shell_quote("a b"), "'a b'");
+    assert_eq!(shell_quote("it's"), "\"it's\"");
+    assert_eq!(shell_quote("a$'b"), "'a$'\\''b'");
+    assert_eq!(shell_quote("a\x01b"), "'a'$'\\001''b'");
+    assert_eq!(shell_quote("\t"), "''$'\\t'");
+    assert_eq!(shell_quote("a\\b\nc"), "'a\\b'$'\\n''c'");
+    assert_eq!(shell_quote("é"), "é");
+}
@@ -49,3 +49,239 @@ fn hello1_no_newline() -> TestResult {
 fn hello2_no_newline() -> TestResult {
     run(&["-n", "Hello", "there"], "tests/expected/hello2.n.txt")
 }
+
+#[test]
+fn escapes_not_interpreted_by_default() -> TestResult {
+    run(&["tab\\there", "new\\nline"], "tests/expected/escapes.txt")
+}
+
+#[test]
+fn escapes_interpreted() -> TestResult {
+    run(
+        &["-e", "tab\\there", "new\\nline"],
+        "tests/expected/escapes.e.txt",
+    )
+}
+
+#[test]
+fn escapes_disabled_by_last_flag() -> TestResult {
+    run(
+        &["-e", "-E", "tab\\there", "new\\nline"],
+        "tests/expected/escapes.E.txt",
+    )
+}
+
+#[test]
+fn escapes_enabled_by_last_flag() -> TestResult {
+    run(&["-E", "-e", "bell\\a"], "tests/expected/bell.Ee.txt")
+}
+
+#[test]
+fn escape_stops_output() -> TestResult {
+    run(
+        &["-e", "stop\\cnot shown", "or this"],
+        "tests/expected/stop.e.txt",
+    )
+}
+
+#[test]
+fn escape_codes() -> TestResult {
+    run(
+        &["-e", "\\0101\\x42\\103 \\e[1mbold\\e[0m \\\\ \\q"],
+        "tests/expected/codes.e.txt",
+    )
+}
+
+#[test]
+fn escapes_combined_with_no_newline() -> TestResult {
+    run(&["-ne", "a\\tb\\n\\vc"], "tests/expected/tab.ne.txt")
+}
+
+fn run_compat(compat: &str, args: &[&str], expected_file: &str) -> TestResult {
+    let expected = fs::read_to_string(expected_file)?;
+    Command::cargo_bin("echor")?
+        .env("ECHOR_COMPAT", compat)
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+#[test]
+fn gnu_options_end_at_first_text() -> TestResult {
+    run_compat(
+        "gnu",
+        &["-ne", "-x", "-n", "a\\tb"],
+        "tests/expected/gnu.options.txt",
+    )
+}
+
+#[test]
+fn gnu_prints_double_dash() -> TestResult {
+    run_compat("gnu", &["--", "-n"], "tests/expected/gnu.dashdash.txt")
+}
+
+#[test]
+fn gnu_help_only_when_alone() -> TestResult {
+    Command::cargo_bin("echor")?
+        .env("ECHOR_COMPAT", "gnu")
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Usage"));
+    Command::cargo_bin("echor")?
+        .env("ECHOR_COMPAT", "gnu")
+        .args(["a", "--help"])
+        .assert()
+        .success()
+        .stdout("a --help\n");
+    Ok(())
+}
+
+#[test]
+fn gnu_without_arguments() -> TestResult {
+    run_compat("gnu", &[], "tests/expected/empty.txt")
+}
+
+#[test]
+fn posix_only_first_n_is_an_option() -> TestResult {
+    run_compat(
+        "posix",
+        &["-n", "a\\tb", "-e"],
+        "tests/expected/posix.n.txt",
+    )
+}
+
+#[test]
+fn posix_options_after_n() -> TestResult {
+    Command::cargo_bin("echor")?
+        .env("ECHOR_COMPAT", "posix")
+        .args(["-n", "-e", "a\\tb"])
+        .assert()
+        .success()
+        .stdout("a\tb");
+    Ok(())
+}
+
+#[test]
+fn posixly_correct_interprets_escapes() -> TestResult {
+    let expected = fs::read_to_string("tests/expected/posix.escapes.txt")?;
+    Command::cargo_bin("echor")?
+        .env_remove("ECHOR_COMPAT")
+        .env("POSIXLY_CORRECT", "1")
+        .args(["-e", "a\\tb\\c", "x"])
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+#[test]
+fn bsd_trailing_c_omits_newline() -> TestResult {
+    run_compat("bsd", &["-e", "a\\tb\\c"], "tests/expected/bsd.c.txt")
+}
+
+#[test]
+fn bsd_only_first_n_is_an_option() -> TestResult {
+    run_compat("bsd", &["-n", "-n"], "tests/expected/bsd.n.txt")
+}
+
+#[test]
+fn dies_bad_compat() -> TestResult {
+    Command::cargo_bin("echor")?
+        .env("ECHOR_COMPAT", "sysv")
+        .arg("hello")
+        .assert()
+        .failure()
+        .stderr("invalid ECHOR_COMPAT -- sysv\n");
+    Ok(())
+}
+
+#[test]
+fn printf_reuses_format() -> TestResult {
+    run(
+        &["--printf", "%s=%d\\n", "a", "1", "b", "2", "c"],
+        "tests/expected/printf.reuse.txt",
+    )
+}
+
+#[test]
+fn printf_numbers() -> TestResult {
+    run(
+        &[
+            "--printf",
+            "%+05d|%-6.2f|%#x|%o|%e|%g|%c|%u\\n",
+            "42",
+            "3.14159",
+            "255",
+            "8",
+            "1234.5",
+            "0.0001",
+            "xyz",
+            "-1",
+        ],
+        "tests/expected/printf.numbers.txt",
+    )
+}
+
+#[test]
+fn printf_escapes() -> TestResult {
+    run(
+        &["--printf", "tab\\there %b|%q\\n", "bell\\a", "a b"],
+        "tests/expected/printf.escapes.txt",
+    )
+}
+
+#[test]
+fn printf_bad_number() -> TestResult {
+    let expected = fs::read_to_string("tests/expected/printf.bad.txt")?;
+    Command::cargo_bin("echor")?
+        .args(["--printf", "%d|%d\\n", "12abc", "x"])
+        .assert()
+        .failure()
+        .stdout(expected)
+        .stderr("'12abc': value not completely converted\n");
+    Ok(())
+}
+
+#[test]
+fn dies_printf_invalid_conversion() -> TestResult {
+    Command::cargo_bin("echor")?
+        .args(["--printf", "%z"])
+        .assert()
+        .failure()
+        .stderr("%z: invalid conversion specification\n");
+    Ok(())
+}
+
+#[test]
+fn dies_printf_huge_width() -> TestResult {
+    Command::cargo_bin("echor")?
+        .args(["--printf", "a%*d|\\n", "99999999999999", "1"])
+        .assert()
+        .failure()
+        .stdout("a")
+        .stderr("invalid field width: '99999999999999'\n");
+    Command::cargo_bin("echor")?
+        .args(["--printf", "%3000000000d|\\n", "1"])
+        .assert()
+        .failure()
+        .stderr("invalid field width: '3000000000'\n");
+    Ok(())
+}
+
+#[test]
+fn dies_printf_huge_precision() -> TestResult {
+    Command::cargo_bin("echor")?
+        .args(["--printf", "%.*f|\\n", "-99999999999", "1"])
+        .assert()
+        .failure()
+        .stderr("invalid precision: '-99999999999'\n");
+    Command::cargo_bin("echor")?
+        .args(["--printf", "%.3000000000s|\\n", "x"])
+        .assert()
+        .failure()
+        .stderr("invalid precision: '3000000000'\n");
+    Ok(())
+}
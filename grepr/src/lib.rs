@@ -0,0 +1,479 @@
+use clap::{Arg, ArgAction, Command};
+use common::{flag, open, Error, MyResult, EXIT_NOT_SELECTED, EXIT_SELECTED, EXIT_TROUBLE};
+use regex::bytes::{Regex, RegexBuilder};
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+#[derive(Debug)]
+pub struct Config {
+    /// Every pattern joined into one, or `None` when there are no patterns
+    /// (e.g. `-f` with an empty file), which matches nothing.
+    pattern: Option<Regex>,
+    files: Vec<String>,
+    recursive: bool,
+    count: bool,
+    invert_match: bool,
+    files_with_matches: bool,
+    files_without_match: bool,
+    line_number: bool,
+    only_matching: bool,
+}
+
+/// Searches every input, returning the exit status: `EXIT_TROUBLE` when an
+/// input could not be read, otherwise whether any line was selected. As
+/// with GNU grep 3.5 and later, that holds for -L too, so listing a file
+/// is no success of its own. Like grep's, errors exit with `EXIT_TROUBLE`
+/// as well.
+pub fn run(config: Config) -> MyResult<i32> {
+    let mut searcher = Searcher {
+        config: &config,
+        with_filename: config.files.len() > 1
+            || (config.recursive && !Path::new(&config.files[0]).is_file()),
+        trouble: false,
+        out: io::stdout().lock(),
+    };
+    let mut selected = false;
+    for filename in &config.files {
+        // "" is the current directory, searched by -r without operands
+        let is_dir = config.recursive && (filename.is_empty() || Path::new(filename).is_dir());
+        selected |= match is_dir {
+            true => searcher.search_dir(Path::new(filename)),
+            false => searcher.search(filename),
+        }
+        .map_err(Error::trouble)?;
+    }
+    Ok(match (searcher.trouble, selected) {
+        (true, _) => EXIT_TROUBLE,
+        (false, true) => EXIT_SELECTED,
+        (false, false) => EXIT_NOT_SELECTED,
+    })
+}
+
+struct Searcher<'a, W: Write> {
+    config: &'a Config,
+    /// Whether output lines start with the name of their file.
+    with_filename: bool,
+    /// Whether an input could not be read.
+    trouble: bool,
+    out: W,
+}
+
+impl<W: Write> Searcher<'_, W> {
+    /// Searches every file below `dir` in name order. Symbolic links found
+    /// on the way are skipped; only those given as operands are followed.
+    /// The empty path stands for the current directory when -r is given
+    /// no operands, so the names printed do not start with "./".
+    fn search_dir(&mut self, dir: &Path) -> MyResult<bool> {
+        let listing = match dir.as_os_str().is_empty() {
+            true => Path::new("."),
+            false => dir,
+        };
+        let mut entries = match fs::read_dir(listing)
+            .and_then(|entries| entries.collect::<io::Result<Vec<_>>>())
+        {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.report(listing.display(), e);
+                return Ok(false);
+            }
+        };
+        entries.sort_by_key(|entry| entry.file_name());
+
+        let mut selected = false;
+        for entry in entries {
+            let path = dir.join(entry.file_name());
+            match entry.file_type() {
+                Ok(kind) if kind.is_dir() => selected |= self.search_dir(&path)?,
+                Ok(kind) if kind.is_symlink() => {}
+                Ok(_) => selected |= self.search(&path.to_string_lossy())?,
+                Err(e) => self.report(path.display(), e),
+            }
+        }
+        Ok(selected)
+    }
+
+    /// Reports an input that cannot be read, which is then skipped.
+    fn report(&mut self, name: impl fmt::Display, e: impl fmt::Display) {
+        eprintln!("{}: {}", name, e);
+        self.trouble = true;
+    }
+
+    /// Searches one input, printing what the options ask for, and returns
+    /// whether any line was selected. Files that cannot be read are
+    /// reported and skipped.
+    fn search(&mut self, filename: &str) -> MyResult<bool> {
+        let name = match filename {
+            "-" => "(standard input)",
+            name => name,
+        };
+        let mut file = match open(filename) {
+            Ok(file) => file,
+            Err(e) => {
+                self.report(name, e);
+                return Ok(false);
+            }
+        };
+        // Like grep, a NUL in the first block or any line read later makes
+        // this a binary file, whose matching lines are not printed
+        let mut binary = match file.fill_buf() {
+            Ok(buf) => buf.contains(&0),
+            Err(e) => {
+                self.report(name, e);
+                return Ok(false);
+            }
+        };
+
+        let config = self.config;
+        let mut count = 0;
+        let mut line_number = 0;
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match file.read_until(b'\n', &mut line) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    self.report(name, e);
+                    break;
+                }
+            }
+            line_number += 1;
+            let text = line.strip_suffix(b"\n").unwrap_or(&line);
+            binary |= text.contains(&0);
+            if is_match(&config.pattern, text) == config.invert_match {
+                continue;
+            }
+
+            count += 1;
+            if config.files_with_matches || config.files_without_match {
+                // The answer is known
+                break;
+            }
+            if config.count {
+                continue;
+            }
+            if binary {
+                self.out.flush()?;
+                eprintln!("{}: binary file matches", name);
+                break;
+            }
+            self.print_line(name, line_number, text)?;
+        }
+
+        if config.files_with_matches || config.files_without_match {
+            if (count > 0) == config.files_with_matches {
+                writeln!(self.out, "{}", name)?;
+            }
+            return Ok(count > 0);
+        }
+        if config.count {
+            if self.with_filename {
+                write!(self.out, "{}:", name)?;
+            }
+            writeln!(self.out, "{}", count)?;
+        }
+        Ok(count > 0)
+    }
+
+    /// Prints a selected line, or with -o each non-empty match in it, after
+    /// the file name and line number when asked for.
+    fn print_line(&mut self, name: &str, line_number: usize, text: &[u8]) -> io::Result<()> {
+        let mut print = |part: &[u8]| -> io::Result<()> {
+            if self.with_filename {
+                write!(self.out, "{}:", name)?;
+            }
+            if self.config.line_number {
+                write!(self.out, "{}:", line_number)?;
+            }
+            self.out.write_all(part)?;
+            self.out.write_all(b"\n")
+        };
+
+        if !self.config.only_matching {
+            return print(text);
+        }
+        // An inverted match has nothing to show
+        let Some(pattern) = self
+            .config
+            .pattern
+            .as_ref()
+            .filter(|_| !self.config.invert_match)
+        else {
+            return Ok(());
+        };
+        let mut start = 0;
+        while start <= text.len() {
+            let Some(found) = pattern.find_at(text, start) else {
+                break;
+            };
+            if found.is_empty() {
+                start = found.end() + 1;
+                continue;
+            }
+            print(found.as_bytes())?;
+            start = found.end();
+        }
+        Ok(())
+    }
+}
+
+fn is_match(pattern: &Option<Regex>, text: &[u8]) -> bool {
+    pattern
+        .as_ref()
+        .is_some_and(|pattern| pattern.is_match(text))
+}
+
+/// Joins `patterns` into one regex that matches wherever any of them does.
+/// With -w a match must not have a word character on either side, and
+/// with -x it must be the whole line. Fails with the first pattern that is
+/// not a valid regex.
+fn build_pattern(
+    patterns: &[String],
+    ignore_case: bool,
+    word: bool,
+    line: bool,
+) -> Result<Option<Regex>, String> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    for pattern in patterns {
+        if Regex::new(pattern).is_err() {
+            return Err(pattern.to_string());
+        }
+    }
+
+    let alternatives: Vec<_> = patterns.iter().map(|p| format!("(?:{})", p)).collect();
+    let joined = alternatives.join("|");
+    let regex = match (line, word) {
+        (true, _) => format!("^(?:{})$", joined),
+        (false, true) => format!(r"\b{{start-half}}(?:{})\b{{end-half}}", joined),
+        (false, false) => joined,
+    };
+    RegexBuilder::new(&regex)
+        .case_insensitive(ignore_case)
+        .build()
+        .map(Some)
+        .map_err(|_| patterns.join("\n"))
+}
+
+/// Reads the patterns in `filename`, one per line.
+fn read_patterns(filename: &str) -> MyResult<Vec<String>> {
+    let file = open(filename).map_err(|e| e.in_file(filename))?;
+    file.lines()
+        .collect::<io::Result<_>>()
+        .map_err(|e| Error::from(e).in_file(filename))
+}
+
+pub fn get_args() -> MyResult<Config> {
+    get_args_from(std::env::args_os())
+}
+
+/// Like `get_args`, but parses `args` (whose first item is the program name)
+/// instead of the process arguments.
+pub fn get_args_from<I, T>(args: I) -> MyResult<Config>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let mut matches = Command::new("grepr")
+        .version("0.1.0")
+        .author("Derek Warner <derekw3@illinois.edu>")
+        .about("A rusty grep")
+        .arg(
+            Arg::new("pattern")
+                .value_name("PATTERN")
+                .help("Search pattern")
+                .required_unless_present_any(["regexp", "file"]),
+        )
+        .arg(
+            Arg::new("files")
+                .value_name("FILE")
+                .help("Input file(s)")
+                .num_args(0..),
+        )
+        .arg(
+            Arg::new("regexp")
+                .short('e')
+                .long("regexp")
+                .value_name("PATTERN")
+                .help("Search for PATTERN; may be given more than once")
+                .allow_hyphen_values(true)
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .long("file")
+                .value_name("FILE")
+                .help("Search for the patterns in FILE, one per line")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("ignore_case")
+                .short('i')
+                .long("ignore-case")
+                .help("Case-insensitive matching")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("invert_match")
+                .short('v')
+                .long("invert-match")
+                .help("Select lines that do not match")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("word_regexp")
+                .short('w')
+                .long("word-regexp")
+                .help("Only match whole words")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("line_regexp")
+                .short('x')
+                .long("line-regexp")
+                .help("Only match whole lines")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("count")
+                .short('c')
+                .long("count")
+                .help("Print only a count of selected lines per file")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("files_with_matches")
+                .short('l')
+                .long("files-with-matches")
+                .help("Print only the names of files with selected lines")
+                .num_args(0)
+                .conflicts_with("files_without_match"),
+        )
+        .arg(
+            Arg::new("files_without_match")
+                .short('L')
+                .long("files-without-match")
+                .help("Print only the names of files without selected lines")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("line_number")
+                .short('n')
+                .long("line-number")
+                .help("Print the line number before each line")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("only_matching")
+                .short('o')
+                .long("only-matching")
+                .help("Print only the matching parts of lines")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("recursive")
+                .short('r')
+                .long("recursive")
+                .help("Search the files in directories, recursively")
+                .num_args(0),
+        )
+        .get_matches_from(args);
+
+    let mut files: Vec<String> = matches.remove_many("files").into_iter().flatten().collect();
+    // With -e or -f the first operand is a file, not the pattern
+    if matches.contains_id("regexp") || matches.contains_id("file") {
+        if let Some(filename) = matches.remove_one("pattern") {
+            files.insert(0, filename);
+        }
+    }
+
+    let mut patterns = Vec::new();
+    if let Some(regexps) = matches.remove_many::<String>("regexp") {
+        patterns.extend(regexps);
+    }
+    if let Some(names) = matches.remove_many::<String>("file") {
+        for name in names {
+            patterns.extend(read_patterns(&name).map_err(Error::trouble)?);
+        }
+    }
+    if let Some(pattern) = matches.remove_one("pattern") {
+        patterns.push(pattern);
+    }
+    // Like grep, a pattern holding newlines is one pattern per line
+    let patterns: Vec<String> = patterns
+        .iter()
+        .flat_map(|pattern| pattern.split('\n'))
+        .map(str::to_string)
+        .collect();
+
+    let recursive = flag(&matches, "recursive");
+    if files.is_empty() {
+        files.push(if recursive { "" } else { "-" }.to_string());
+    }
+
+    let pattern = build_pattern(
+        &patterns,
+        flag(&matches, "ignore_case"),
+        flag(&matches, "word_regexp"),
+        flag(&matches, "line_regexp"),
+    )
+    .map_err(|e| Error::Usage(format!("illegal pattern -- {}", e)).trouble())?;
+
+    Ok(Config {
+        pattern,
+        files,
+        recursive,
+        count: flag(&matches, "count"),
+        invert_match: flag(&matches, "invert_match"),
+        files_with_matches: flag(&matches, "files_with_matches"),
+        files_without_match: flag(&matches, "files_without_match"),
+        line_number: flag(&matches, "line_number"),
+        only_matching: flag(&matches, "only_matching"),
+    })
+}
+
+#[test]
+fn test_build_pattern() {
+    let build = |patterns: &[&str], ignore_case, word, line| {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        build_pattern(&patterns, ignore_case, word, line)
+    };
+    let matches = |pattern: &Option<Regex>, text: &str| is_match(pattern, text.as_bytes());
+
+    let fox = build(&["fox", "^dog"], false, false, false).unwrap();
+    assert!(matches(&fox, "the fox"));
+    assert!(matches(&fox, "dogs"));
+    assert!(!matches(&fox, "hot dog"));
+    assert!(!matches(&fox, "FOX"));
+
+    let fox = build(&["fox"], true, false, false).unwrap();
+    assert!(matches(&fox, "FOX"));
+
+    let word = build(&["ab|abc", "@x"], false, true, false).unwrap();
+    assert!(matches(&word, "abc"));
+    assert!(matches(&word, "(ab)"));
+    assert!(!matches(&word, "abcd"));
+    assert!(!matches(&word, "_ab"));
+    // Only the sides of a match are checked, not what it starts or ends with
+    assert!(matches(&word, "a @x"));
+    assert!(!matches(&word, "a@xy"));
+
+    let line = build(&["a|b", "c"], false, true, true).unwrap();
+    assert!(matches(&line, "b"));
+    assert!(matches(&line, "c"));
+    assert!(!matches(&line, "ab"));
+
+    // No patterns match nothing, an empty one everything
+    assert!(!matches(&build(&[], false, false, false).unwrap(), ""));
+    assert!(matches(&build(&[""], false, false, false).unwrap(), "x"));
+
+    assert_eq!(
+        build(&["ok", "a(b", "c["], false, false, false).unwrap_err(),
+        "a(b"
+    );
+}
@@ -0,0 +1,10 @@
+fn main() {
+    // Like grep, the status tells whether anything was selected
+    match grepr::get_args().and_then(grepr::run) {
+        Ok(status) => std::process::exit(status),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(e.exit_code());
+        }
+    }
+}
@@ -0,0 +1,398 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use rand::{distributions::Alphanumeric, Rng};
+use std::{error::Error, fs};
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+const PRG: &str = "grepr";
+const BUSTLE: &str = "tests/inputs/the-bustle.txt";
+const EMPTY: &str = "tests/inputs/empty.txt";
+const FOX: &str = "tests/inputs/fox.txt";
+const SPIDERS: &str = "tests/inputs/spiders.txt";
+const BINARY: &str = "tests/inputs/binary.bin";
+const PATTERNS: &str = "tests/inputs/patterns.txt";
+const INPUTS: &str = "tests/inputs";
+
+// --------------------------------------------------
+fn random_string() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(7)
+        .map(char::from)
+        .collect()
+}
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename = random_string();
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_no_pattern() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the following required arguments were not provided",
+        ));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_pattern() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-e", "ok", "-e", "*foo", BUSTLE])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("illegal pattern -- *foo"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_pattern_file() -> TestResult {
+    let bad = gen_bad_file();
+    let expected = format!("{}: .* [(]os error 2[)]", bad);
+    Command::cargo_bin(PRG)?
+        .args(["-f", &bad, BUSTLE])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::is_match(expected)?);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_l_and_upper_l() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-l", "-L", "the", BUSTLE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file() -> TestResult {
+    let bad = gen_bad_file();
+    let expected = format!("{}: .* [(]os error 2[)]", bad);
+    Command::cargo_bin(PRG)?
+        .args(["fox", &bad, FOX])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::is_match(expected)?)
+        .stdout(format!(
+            "{}:The quick brown fox jumps over the lazy dog.\n",
+            FOX
+        ));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_directory() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["fox", INPUTS, FOX])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(format!(
+            "{}: Is a directory",
+            INPUTS
+        )))
+        .stdout(format!(
+            "{}:The quick brown fox jumps over the lazy dog.\n",
+            FOX
+        ));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn fails_without_match() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["foo", EMPTY])
+        .assert()
+        .code(1)
+        .stdout(fs::read("tests/expected/empty.txt.out")?);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+// As with GNU grep 3.5 and later, -L succeeds when a line was selected
+// rather than when a file was listed
+#[test]
+fn files_without_match_status() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-L", "the", BUSTLE])
+        .assert()
+        .success()
+        .stdout("");
+    Command::cargo_bin(PRG)?
+        .args(["-L", "the", EMPTY])
+        .assert()
+        .code(1)
+        .stdout(format!("{}\n", EMPTY));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> TestResult {
+    let expected = fs::read(expected_file)?;
+
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run_stdin(args: &[&str], input_file: &str, expected_file: &str) -> TestResult {
+    let expected = fs::read(expected_file)?;
+    let input = fs::read(input_file)?;
+
+    Command::cargo_bin(PRG)?
+        .write_stdin(input)
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle() -> TestResult {
+    run(&["The", BUSTLE], "tests/expected/the-bustle.txt.the.out")
+}
+
+#[test]
+fn bustle_stdin() -> TestResult {
+    run_stdin(&["The"], BUSTLE, "tests/expected/the-bustle.txt.the.out")
+}
+
+#[test]
+fn bustle_dash() -> TestResult {
+    run_stdin(
+        &["The", "-"],
+        BUSTLE,
+        "tests/expected/the-bustle.txt.the.out",
+    )
+}
+
+#[test]
+fn bustle_insensitive() -> TestResult {
+    run(
+        &["-i", "the", BUSTLE],
+        "tests/expected/the-bustle.txt.the.i.out",
+    )
+}
+
+#[test]
+fn bustle_invert() -> TestResult {
+    run(
+        &["-v", "The", BUSTLE],
+        "tests/expected/the-bustle.txt.the.v.out",
+    )
+}
+
+#[test]
+fn bustle_count() -> TestResult {
+    run(
+        &["-c", "the", BUSTLE],
+        "tests/expected/the-bustle.txt.the.c.out",
+    )
+}
+
+#[test]
+fn bustle_count_insensitive() -> TestResult {
+    run(
+        &["-ic", "the", BUSTLE],
+        "tests/expected/the-bustle.txt.the.ic.out",
+    )
+}
+
+#[test]
+fn bustle_line_number() -> TestResult {
+    run(
+        &["-n", "the", BUSTLE],
+        "tests/expected/the-bustle.txt.the.n.out",
+    )
+}
+
+#[test]
+fn bustle_only_matching() -> TestResult {
+    run(
+        &["-o", "the", BUSTLE],
+        "tests/expected/the-bustle.txt.the.o.out",
+    )
+}
+
+#[test]
+fn bustle_only_matching_numbered() -> TestResult {
+    run(
+        &["-ino", "the", BUSTLE],
+        "tests/expected/the-bustle.txt.the.in.o.out",
+    )
+}
+
+#[test]
+fn bustle_word() -> TestResult {
+    run(
+        &["-w", "the", BUSTLE],
+        "tests/expected/the-bustle.txt.the.w.out",
+    )
+}
+
+#[test]
+fn bustle_word_insensitive() -> TestResult {
+    run(
+        &["-iw", "the", BUSTLE],
+        "tests/expected/the-bustle.txt.the.iw.out",
+    )
+}
+
+#[test]
+fn bustle_line() -> TestResult {
+    run(
+        &["-x", "Until eternity.", BUSTLE],
+        "tests/expected/the-bustle.txt.x.out",
+    )
+}
+
+#[test]
+fn bustle_empty_line() -> TestResult {
+    run(
+        &["-x", "", BUSTLE],
+        "tests/expected/the-bustle.txt.empty.x.out",
+    )
+}
+
+#[test]
+fn bustle_patterns() -> TestResult {
+    run(
+        &["-e", "heart", "-e", "love", BUSTLE],
+        "tests/expected/the-bustle.txt.e.out",
+    )
+}
+
+#[test]
+fn bustle_patterns_only_matching() -> TestResult {
+    run(
+        &["-on", "-e", "heart|love", "-e", "up[a-z]*", BUSTLE],
+        "tests/expected/the-bustle.txt.e.on.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn all() -> TestResult {
+    run(&["the", BUSTLE, FOX, SPIDERS], "tests/expected/all.the.out")
+}
+
+#[test]
+fn all_count() -> TestResult {
+    run(
+        &["-c", "the", BUSTLE, FOX, SPIDERS],
+        "tests/expected/all.the.c.out",
+    )
+}
+
+#[test]
+fn all_files_with_matches() -> TestResult {
+    run(
+        &["-l", "house", BUSTLE, FOX, SPIDERS, EMPTY],
+        "tests/expected/all.the.l.out",
+    )
+}
+
+#[test]
+fn all_files_without_match() -> TestResult {
+    run(
+        &["-L", "house", BUSTLE, FOX, SPIDERS, EMPTY],
+        "tests/expected/all.the.L.out",
+    )
+}
+
+#[test]
+fn all_pattern_file() -> TestResult {
+    run(
+        &["-f", PATTERNS, BUSTLE, FOX, SPIDERS],
+        "tests/expected/all.f.out",
+    )
+}
+
+#[test]
+fn all_pattern_file_invert() -> TestResult {
+    run(
+        &["-vn", "-f", PATTERNS, BUSTLE, FOX, SPIDERS],
+        "tests/expected/all.f.vn.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn binary() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["line", BINARY])
+        .assert()
+        .success()
+        .stderr(format!("{}: binary file matches\n", BINARY))
+        .stdout(fs::read("tests/expected/binary.bin.out")?);
+
+    Ok(())
+}
+
+#[test]
+fn binary_count() -> TestResult {
+    run(&["-c", "line", BINARY], "tests/expected/binary.bin.c.out")
+}
+
+// --------------------------------------------------
+// GNU grep lists directories in the order it reads them; these expected
+// files are in name order, which grepr uses
+#[test]
+fn recursive() -> TestResult {
+    run(&["-r", "house", INPUTS], "tests/expected/recursive.out")
+}
+
+#[test]
+fn recursive_numbered() -> TestResult {
+    run(
+        &["-rin", "dog|you", INPUTS],
+        "tests/expected/recursive.in.out",
+    )
+}
+
+#[test]
+fn recursive_current_dir() -> TestResult {
+    let expected = fs::read("tests/expected/recursive.cwd.c.out")?;
+    Command::cargo_bin(PRG)?
+        .current_dir(INPUTS)
+        .args(["-rc", "the"])
+        .assert()
+        .success()
+        .stdout(expected);
+
+    Ok(())
+}
@@ -1,49 +1,71 @@
-use clap::{parser::ValueSource, Arg, Command};
-use std::fs::File;
-use std::{
-    error::Error,
-    io::{self, BufRead, BufReader, Read},
+use clap::{Arg, Command};
+use common::{
+    flag, open_file, parse_size, seekable_range, start_of_last_lines, Error, MyResult, BLOCK_SIZE,
 };
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
 
-type MyResult<T> = Result<T, Box<dyn Error>>;
+/// How much of each input -n or -c selects.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Count {
+    /// The first N lines or bytes.
+    First(usize),
+    /// Everything except the last N lines or bytes.
+    AllButLast(usize),
+}
 
 #[derive(Debug)]
 pub struct Config {
     files: Vec<String>,
-    lines: usize,
-    bytes: Option<usize>,
+    lines: Count,
+    bytes: Option<Count>,
+    quiet: bool,
+    verbose: bool,
 }
 
 pub fn run(config: Config) -> MyResult<()> {
+    let mut stdout = io::stdout().lock();
     let mut file_count = 0;
     let num_files = config.files.len();
+    let print_headers = config.verbose || (!config.quiet && num_files > 1);
     for filename in config.files {
-        match open(&filename) {
+        match open_file(&filename) {
             Err(e) => eprintln!("{}: {}", filename, e),
-            Ok(mut file) => {
-                if file_count != 0 {
-                    println!();
-                }
-                if num_files > 1 {
-                    println!("==> {} <==", filename);
+            Ok(file) => {
+                if print_headers {
+                    if file_count != 0 {
+                        writeln!(stdout)?;
+                    }
+                    let name = match filename.as_str() {
+                        "-" => "standard input",
+                        name => name,
+                    };
+                    writeln!(stdout, "==> {} <==", name)?;
                 }
-                match config.bytes {
-                    Some(bytes) => {
-                        let bytes = file.bytes().take(bytes).collect::<Result<Vec<_>, _>>();
-
-                        print!("{}", String::from_utf8_lossy(&bytes?));
+                match (config.bytes, config.lines) {
+                    (Some(Count::First(bytes)), _) => {
+                        io::copy(&mut file.take(bytes as u64), &mut stdout)?;
                     }
-                    None => {
-                        let mut line = String::new();
-                        for _ in 0..config.lines {
-                            let bytes = file.read_line(&mut line)?;
+                    (Some(Count::AllButLast(bytes)), _) => {
+                        all_but_last_bytes(file, bytes, &mut stdout)?
+                    }
+                    (None, Count::First(lines)) => {
+                        let mut file = BufReader::new(file);
+                        let mut line = Vec::new();
+                        for _ in 0..lines {
+                            let bytes = file.read_until(b'\n', &mut line)?;
                             if bytes == 0 {
                                 break;
                             }
-                            print!("{}", line);
+                            stdout.write_all(&line)?;
                             line.clear();
                         }
                     }
+                    (None, Count::AllButLast(lines)) => {
+                        all_but_last_lines(file, lines, &mut stdout)?
+                    }
                 }
                 file_count += 1;
             }
@@ -52,21 +74,103 @@ pub fn run(config: Config) -> MyResult<()> {
     Ok(())
 }
 
-fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+/// Prints all but the last `count` bytes of `file`.
+fn all_but_last_bytes(mut file: File, count: usize, out: &mut impl Write) -> MyResult<()> {
+    if let Some((start, end)) = seekable_range(&mut file)? {
+        let keep = (end - start).saturating_sub(count as u64);
+        io::copy(&mut file.take(keep), out)?;
+        return Ok(());
+    }
+
+    // Hold back the most recent `count` bytes until the end is known
+    let mut window = VecDeque::new();
+    let mut buffer = vec![0; BLOCK_SIZE];
+    loop {
+        let bytes = file.read(&mut buffer)?;
+        if bytes == 0 {
+            break;
+        }
+        window.extend(&buffer[..bytes]);
+        if window.len() > count {
+            let excess = window.len() - count;
+            let (front, back) = window.as_slices();
+            let from_front = excess.min(front.len());
+            out.write_all(&front[..from_front])?;
+            out.write_all(&back[..excess - from_front])?;
+            window.drain(..excess);
+        }
+    }
+    Ok(())
+}
+
+/// Prints all but the last `count` lines of `file`. A final line without a
+/// newline still counts as a line.
+fn all_but_last_lines(mut file: File, count: usize, out: &mut impl Write) -> MyResult<()> {
+    if let Some((start, end)) = seekable_range(&mut file)? {
+        let cut = start_of_last_lines(&mut file, start, end, count)?;
+        file.seek(SeekFrom::Start(start))?;
+        io::copy(&mut file.take(cut - start), out)?;
+        return Ok(());
+    }
+
+    // Hold back the most recent `count` lines until the end is known
+    let mut file = BufReader::new(file);
+    let mut window: VecDeque<Vec<u8>> = VecDeque::new();
+    loop {
+        let mut line = match window.len() {
+            len if len > count => window.pop_front().unwrap(),
+            _ => Vec::new(),
+        };
+        line.clear();
+        if file.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        window.push_back(line);
+        if window.len() > count {
+            out.write_all(&window[0])?;
+        }
     }
+    Ok(())
 }
 
+/// Parses a count of at least one, which may carry a size suffix such as
+/// `4K` or `1MiB` (see `common::parse_size`).
 pub fn parse_positive_int(val: &str) -> MyResult<usize> {
-    match val.parse() {
-        Ok(n) if n > 0 => Ok(n),
-        _ => Err(From::from(val)),
+    match parse_size(val).and_then(|n| to_usize(val, n))? {
+        0 => Err(Error::Usage(val.to_string())),
+        n => Ok(n),
+    }
+}
+
+fn to_usize(val: &str, n: u64) -> MyResult<usize> {
+    usize::try_from(n)
+        .map_err(|_| Error::Usage(format!("{}: Value too large for defined data type", val)))
+}
+
+/// Parses the value of -n or -c; a leading "-" selects everything but the
+/// last N lines or bytes.
+pub fn parse_count(val: &str) -> MyResult<Count> {
+    match val.strip_prefix('-') {
+        // The message names the value after the "-", so put it back
+        Some(n) => parse_size(n)
+            .map_err(|e| Error::Usage(format!("-{}", e)))
+            .and_then(|count| to_usize(val, count))
+            .map(Count::AllButLast),
+        None => parse_positive_int(val).map(Count::First),
     }
 }
 
 pub fn get_args() -> MyResult<Config> {
+    get_args_from(std::env::args_os())
+}
+
+/// Like `get_args`, but parses `args` (whose first item is the program name)
+/// instead of the process arguments.
+pub fn get_args_from<I, T>(args: I) -> MyResult<Config>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
     let mut matches = Command::new("headr")
         .version("0.1.0")
         .author("Derek Warner <derekw3@illinois.edu>")
@@ -84,6 +188,7 @@ pub fn get_args() -> MyResult<Config> {
                 .value_name("LINES")
                 .help("Number of lines")
                 .num_args(1)
+                .allow_hyphen_values(true)
                 .default_value("10"),
         )
         .arg(
@@ -93,40 +198,51 @@ pub fn get_args() -> MyResult<Config> {
                 .value_name("BYTES")
                 .help("Number of bytes")
                 .num_args(1)
+                .allow_hyphen_values(true)
                 .default_value("10")
                 .conflicts_with("number_lines"),
         )
-        .get_matches();
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .visible_alias("silent")
+                .help("Never print headers giving file names")
+                .num_args(0)
+                .overrides_with("verbose"),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("Always print headers giving file names")
+                .num_args(0)
+                .overrides_with("quiet"),
+        )
+        .get_matches_from(args);
 
     let files_vec: Vec<String> = matches.remove_many("files").unwrap().collect();
 
-    let number_lines_flag = matches!(
-        matches.value_source("number_lines").unwrap(),
-        ValueSource::CommandLine
-    );
-
-    let number_bytes_flag = matches!(
-        matches.value_source("bytes").unwrap(),
-        ValueSource::CommandLine
-    );
+    let number_lines_flag = flag(&matches, "number_lines");
+    let number_bytes_flag = flag(&matches, "bytes");
 
-    let mut number_lines: usize = 10;
+    let mut number_lines = Count::First(10);
     if number_lines_flag {
         let input_number_string: String = matches.remove_one("number_lines").unwrap();
-        let input_number_lines = parse_positive_int(&input_number_string);
+        let input_number_lines = parse_count(&input_number_string);
         match input_number_lines {
             Ok(num) => number_lines = num,
-            Err(e) => return Err(e).map_err(|e| format!("illegal line count -- {}", e))?,
+            Err(e) => return Err(Error::Usage(format!("illegal line count -- {}", e))),
         }
     }
 
-    let mut number_bytes: Option<usize> = None;
+    let mut number_bytes: Option<Count> = None;
     if number_bytes_flag {
         let input_bytes_string: String = matches.remove_one("bytes").unwrap();
-        let input_number_bytes = parse_positive_int(&input_bytes_string);
+        let input_number_bytes = parse_count(&input_bytes_string);
         match input_number_bytes {
             Ok(num) => number_bytes = Some(num),
-            Err(e) => return Err(e).map_err(|e| format!("illegal byte count -- {}", e))?,
+            Err(e) => return Err(Error::Usage(format!("illegal byte count -- {}", e))),
         }
     }
 
@@ -134,6 +250,8 @@ pub fn get_args() -> MyResult<Config> {
         files: files_vec,
         lines: number_lines,
         bytes: number_bytes,
+        quiet: flag(&matches, "quiet"),
+        verbose: flag(&matches, "verbose"),
     })
 }
 
@@ -152,4 +270,39 @@ fn test_parse_positive_int() {
     let result = parse_positive_int("0");
     assert!(result.is_err());
     assert_eq!(result.unwrap_err().to_string(), "0".to_string());
+
+    // Size suffixes scale the count
+    assert_eq!(parse_positive_int("4K").unwrap(), 4096);
+    assert_eq!(parse_positive_int("1MiB").unwrap(), 1 << 20);
+    assert_eq!(parse_positive_int("2kB").unwrap(), 2000);
+    assert_eq!(parse_positive_int("1b").unwrap(), 512);
+
+    let result = parse_positive_int("4X");
+    assert!(result.is_err());
+    assert_eq!(result.unwrap_err().to_string(), "4X".to_string());
+}
+
+#[test]
+fn test_parse_count() {
+    assert_eq!(parse_count("3").unwrap(), Count::First(3));
+    assert_eq!(parse_count("-3").unwrap(), Count::AllButLast(3));
+    assert_eq!(parse_count("-0").unwrap(), Count::AllButLast(0));
+
+    let result = parse_count("-testy");
+    assert!(result.is_err());
+    assert_eq!(result.unwrap_err().to_string(), "-testy".to_string());
+
+    assert_eq!(parse_count("-2K").unwrap(), Count::AllButLast(2048));
+    assert_eq!(parse_count("1kB").unwrap(), Count::First(1000));
+
+    let result = parse_count("-16E");
+    assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "-16E: Value too large for defined data type".to_string()
+    );
+
+    // Zero lines from the front is still not valid
+    assert!(parse_count("0").is_err());
+    assert!(parse_count("0K").is_err());
 }
@@ -1,6 +1,6 @@
 fn main() {
     if let Err(e) = headr::get_args().and_then(headr::run) {
         eprintln!("{}", e);
-        std::process::exit(1);
+        std::process::exit(e.exit_code());
     }
 }
@@ -1,11 +1,7 @@
 use assert_cmd::Command;
 use predicates::prelude::*;
 use rand::{distributions::Alphanumeric, Rng};
-use std::io::prelude::*;
-use std::{
-    error::Error,
-    fs::{self, File},
-};
+use std::{error::Error, fs};
 
 type TestResult = Result<(), Box<dyn Error>>;
 
@@ -15,6 +11,8 @@ const ONE: &str = "./tests/inputs/one.txt";
 const TWO: &str = "./tests/inputs/two.txt";
 const THREE: &str = "./tests/inputs/three.txt";
 const TEN: &str = "./tests/inputs/ten.txt";
+const BINARY: &str = "./tests/inputs/binary.bin";
+const NUMBERS: &str = "./tests/inputs/numbers.txt";
 
 // --------------------------------------------------
 fn random_string() -> String {
@@ -41,7 +39,7 @@ fn dies_bad_bytes() -> TestResult {
     let bad = random_string();
     let expected = format!("illegal byte count -- {}", &bad);
     Command::cargo_bin(PRG)?
-        .args(&["-c", &bad, EMPTY])
+        .args(["-c", &bad, EMPTY])
         .assert()
         .failure()
         .stderr(predicate::str::contains(expected));
@@ -55,7 +53,32 @@ fn dies_bad_lines() -> TestResult {
     let bad = random_string();
     let expected = format!("illegal line count -- {}", &bad);
     Command::cargo_bin(PRG)?
-        .args(&["-n", &bad, EMPTY])
+        .args(["-n", &bad, EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(expected));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_size_suffix() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-c", "4X", EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("illegal byte count -- 4X"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_count_too_large() -> TestResult {
+    let expected = "illegal line count -- 20E: Value too large for defined data type";
+    Command::cargo_bin(PRG)?
+        .args(["-n", "20E", EMPTY])
         .assert()
         .failure()
         .stderr(predicate::str::contains(expected));
@@ -70,7 +93,7 @@ fn dies_bytes_and_lines() -> TestResult {
                used with '--bytes <BYTES>'";
 
     Command::cargo_bin(PRG)?
-        .args(&["-n", "1", "-c", "2"])
+        .args(["-n", "1", "-c", "2"])
         .assert()
         .failure()
         .stderr(predicate::str::contains(msg));
@@ -93,35 +116,27 @@ fn skips_bad_file() -> TestResult {
 
 // --------------------------------------------------
 fn run(args: &[&str], expected_file: &str) -> TestResult {
-    // Extra work here due to lossy UTF
-    let mut file = File::open(expected_file)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
-    let expected = String::from_utf8_lossy(&buffer);
+    let expected = fs::read(expected_file)?;
 
     Command::cargo_bin(PRG)?
         .args(args)
         .assert()
         .success()
-        .stdout(predicate::eq(&expected.as_bytes() as &[u8]));
+        .stdout(expected);
 
     Ok(())
 }
 
 // --------------------------------------------------
 fn run_stdin(args: &[&str], input_file: &str, expected_file: &str) -> TestResult {
-    // Extra work here due to lossy UTF
-    let mut file = File::open(expected_file)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
-    let expected = String::from_utf8_lossy(&buffer);
-    let input = fs::read_to_string(input_file)?;
+    let expected = fs::read(expected_file)?;
+    let input = fs::read(input_file)?;
 
     Command::cargo_bin(PRG)?
         .write_stdin(input)
         .args(args)
         .assert()
-        .stdout(predicate::eq(&expected.as_bytes() as &[u8]));
+        .stdout(expected);
 
     Ok(())
 }
@@ -415,3 +430,278 @@ fn multiple_files_c4() -> TestResult {
         "tests/expected/all.c4.out",
     )
 }
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_all_but_last_lines() -> TestResult {
+    let bad = format!("-{}", random_string());
+    let expected = format!("illegal line count -- {}", &bad);
+    Command::cargo_bin(PRG)?
+        .args([&format!("-n{}", bad), EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(expected));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn empty_n_minus2() -> TestResult {
+    run(&[EMPTY, "-n", "-2"], "tests/expected/empty.txt.n-2.out")
+}
+
+#[test]
+fn empty_c_minus4() -> TestResult {
+    run(&[EMPTY, "-c", "-4"], "tests/expected/empty.txt.c-4.out")
+}
+
+#[test]
+fn empty_n_minus2_stdin() -> TestResult {
+    run_stdin(&["-n", "-2"], EMPTY, "tests/expected/empty.txt.n-2.out")
+}
+
+#[test]
+fn empty_c_minus4_stdin() -> TestResult {
+    run_stdin(&["-c", "-4"], EMPTY, "tests/expected/empty.txt.c-4.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn one_n_minus2() -> TestResult {
+    run(&[ONE, "-n", "-2"], "tests/expected/one.txt.n-2.out")
+}
+
+#[test]
+fn one_c_minus4() -> TestResult {
+    run(&[ONE, "-c", "-4"], "tests/expected/one.txt.c-4.out")
+}
+
+#[test]
+fn one_n_minus2_stdin() -> TestResult {
+    run_stdin(&["-n", "-2"], ONE, "tests/expected/one.txt.n-2.out")
+}
+
+#[test]
+fn one_c_minus4_stdin() -> TestResult {
+    run_stdin(&["-c", "-4"], ONE, "tests/expected/one.txt.c-4.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn two_n_minus2() -> TestResult {
+    run(&[TWO, "-n", "-2"], "tests/expected/two.txt.n-2.out")
+}
+
+#[test]
+fn two_c_minus4() -> TestResult {
+    run(&[TWO, "-c", "-4"], "tests/expected/two.txt.c-4.out")
+}
+
+#[test]
+fn two_n_minus2_stdin() -> TestResult {
+    run_stdin(&["-n", "-2"], TWO, "tests/expected/two.txt.n-2.out")
+}
+
+#[test]
+fn two_c_minus4_stdin() -> TestResult {
+    run_stdin(&["-c", "-4"], TWO, "tests/expected/two.txt.c-4.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn three_n_minus2() -> TestResult {
+    run(&[THREE, "-n", "-2"], "tests/expected/three.txt.n-2.out")
+}
+
+#[test]
+fn three_c_minus4() -> TestResult {
+    run(&[THREE, "-c", "-4"], "tests/expected/three.txt.c-4.out")
+}
+
+#[test]
+fn three_n_minus2_stdin() -> TestResult {
+    run_stdin(&["-n", "-2"], THREE, "tests/expected/three.txt.n-2.out")
+}
+
+#[test]
+fn three_c_minus4_stdin() -> TestResult {
+    run_stdin(&["-c", "-4"], THREE, "tests/expected/three.txt.c-4.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn ten_n_minus2() -> TestResult {
+    run(&[TEN, "-n", "-2"], "tests/expected/ten.txt.n-2.out")
+}
+
+#[test]
+fn ten_c_minus4() -> TestResult {
+    run(&[TEN, "-c", "-4"], "tests/expected/ten.txt.c-4.out")
+}
+
+#[test]
+fn ten_n_minus2_stdin() -> TestResult {
+    run_stdin(&["-n", "-2"], TEN, "tests/expected/ten.txt.n-2.out")
+}
+
+#[test]
+fn ten_c_minus4_stdin() -> TestResult {
+    run_stdin(&["-c", "-4"], TEN, "tests/expected/ten.txt.c-4.out")
+}
+
+#[test]
+fn ten_n_minus0() -> TestResult {
+    run(&[TEN, "-n-0"], "tests/expected/ten.txt.n-0.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_files_n_minus3() -> TestResult {
+    run(
+        &["-n", "-3", EMPTY, ONE, TWO, THREE, TEN],
+        "tests/expected/all.n-3.out",
+    )
+}
+
+#[test]
+fn multiple_files_c_minus6() -> TestResult {
+    run(
+        &[EMPTY, ONE, TWO, THREE, TEN, "--bytes=-6"],
+        "tests/expected/all.c-6.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_c8() -> TestResult {
+    run(&[BINARY, "-c", "8"], "tests/expected/binary.bin.c8.out")
+}
+
+#[test]
+fn binary_c30() -> TestResult {
+    run(&[BINARY, "-c", "30"], "tests/expected/binary.bin.c30.out")
+}
+
+#[test]
+fn binary_n2() -> TestResult {
+    run(&[BINARY, "-n", "2"], "tests/expected/binary.bin.n2.out")
+}
+
+#[test]
+fn binary_c8_stdin() -> TestResult {
+    run_stdin(&["-c", "8"], BINARY, "tests/expected/binary.bin.c8.out")
+}
+
+#[test]
+fn binary_n2_stdin() -> TestResult {
+    run_stdin(&["-n", "2"], BINARY, "tests/expected/binary.bin.n2.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_files_quiet() -> TestResult {
+    run(
+        &["-q", EMPTY, ONE, TWO, THREE, TEN],
+        "tests/expected/all.q.out",
+    )
+}
+
+#[test]
+fn multiple_files_silent_n2() -> TestResult {
+    run(
+        &["--silent", "-n", "2", EMPTY, ONE, TWO, THREE, TEN],
+        "tests/expected/all.q.n2.out",
+    )
+}
+
+#[test]
+fn multiple_files_verbose_then_quiet() -> TestResult {
+    run(
+        &["-v", "-q", EMPTY, ONE, TWO, THREE, TEN],
+        "tests/expected/all.vq.out",
+    )
+}
+
+#[test]
+fn one_quiet_then_verbose() -> TestResult {
+    run(&["-q", "-v", ONE], "tests/expected/one.txt.qv.out")
+}
+
+#[test]
+fn ten_verbose() -> TestResult {
+    run(&["-v", TEN], "tests/expected/ten.txt.v.out")
+}
+
+#[test]
+fn two_verbose_c4() -> TestResult {
+    run(
+        &["--verbose", "-c", "4", TWO],
+        "tests/expected/two.txt.v.c4.out",
+    )
+}
+
+#[test]
+fn three_verbose_stdin() -> TestResult {
+    run_stdin(&["-v"], THREE, "tests/expected/three.txt.v.stdin.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn numbers_c1k() -> TestResult {
+    run(&[NUMBERS, "-c", "1K"], "tests/expected/numbers.txt.c1K.out")
+}
+
+#[test]
+fn numbers_c2kb() -> TestResult {
+    run(
+        &[NUMBERS, "-c", "2kB"],
+        "tests/expected/numbers.txt.c2kB.out",
+    )
+}
+
+#[test]
+fn numbers_c1b() -> TestResult {
+    run(&[NUMBERS, "-c", "1b"], "tests/expected/numbers.txt.c1b.out")
+}
+
+#[test]
+fn numbers_n1k() -> TestResult {
+    run(&[NUMBERS, "-n", "1k"], "tests/expected/numbers.txt.n1k.out")
+}
+
+#[test]
+fn numbers_c_minus1kib() -> TestResult {
+    run(
+        &[NUMBERS, "-c", "-1KiB"],
+        "tests/expected/numbers.txt.c-1KiB.out",
+    )
+}
+
+#[test]
+fn numbers_n_minus1k_stdin() -> TestResult {
+    run_stdin(
+        &["-n", "-1K"],
+        NUMBERS,
+        "tests/expected/numbers.txt.n-1K.out",
+    )
+}
+
+// A window as large as K must not be allocated up front
+#[test]
+fn ten_huge_minus_stdin() -> TestResult {
+    for args in [
+        ["-c", "-1000000000000000"],
+        ["-c", "-1E"],
+        ["-n", "-1000000000000000000"],
+    ] {
+        Command::cargo_bin(PRG)?
+            .write_stdin(fs::read(TEN)?)
+            .args(args)
+            .assert()
+            .success()
+            .stdout("");
+    }
+
+    Ok(())
+}
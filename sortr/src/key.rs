@@ -0,0 +1,537 @@
+//! Sort keys: the part of a line `-k` selects and how it is ordered. Keys
+//! are located and compared byte by byte, as in the C locale.
+
+use std::cmp::Ordering;
+
+/// How a key's text is compared.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Kind {
+    #[default]
+    Text,
+    /// -n: a decimal number, optionally negative.
+    Numeric,
+    /// -h: a number with an SI suffix, e.g. 2K or 1G.
+    Human,
+    /// -V: numbers within the text compare by value.
+    Version,
+}
+
+/// The ordering options of a key, or the global ones given as flags.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Options {
+    pub kind: Kind,
+    /// Ignore blanks before the start of the key (b on the start position).
+    pub skip_start_blanks: bool,
+    /// Ignore blanks before the end position (b on the end position).
+    pub skip_end_blanks: bool,
+    pub fold_case: bool,
+    pub reverse: bool,
+}
+
+impl Options {
+    /// Applies option letter `c`, as in `-k 2nr`; `false` when it is not
+    /// one or clashes with the kind already set.
+    fn set(&mut self, c: char, end: bool) -> bool {
+        let kind = match c {
+            'b' if end => {
+                self.skip_end_blanks = true;
+                return true;
+            }
+            'b' => {
+                self.skip_start_blanks = true;
+                return true;
+            }
+            'f' => {
+                self.fold_case = true;
+                return true;
+            }
+            'r' => {
+                self.reverse = true;
+                return true;
+            }
+            'n' => Kind::Numeric,
+            'h' => Kind::Human,
+            'V' => Kind::Version,
+            _ => return false,
+        };
+        let clashes = self.kind != Kind::Text && self.kind != kind;
+        self.kind = kind;
+        !clashes
+    }
+}
+
+/// A sort key from `-k START[,END]`. Fields and characters are counted
+/// from zero here, though given from one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Key {
+    start_field: usize,
+    start_char: usize,
+    /// The last field and the character in it the key runs to, where
+    /// character 0 is the end of the field. `None` runs to the end of the
+    /// line.
+    end: Option<(usize, usize)>,
+    pub options: Options,
+}
+
+impl Key {
+    /// The whole line, ordered by `options`.
+    pub fn line(options: Options) -> Key {
+        Key {
+            start_field: 0,
+            start_char: 0,
+            end: None,
+            options,
+        }
+    }
+
+    /// Parses a `-k` value, e.g. `2`, `2,2`, `1.3b,1.5` or `3nr`. A key
+    /// without options of its own takes on the `global` ones.
+    pub fn parse(spec: &str, global: Options) -> Result<Key, String> {
+        let invalid = || spec.to_string();
+        let (start, end) = match spec.split_once(',') {
+            Some((start, end)) => (start, Some(end)),
+            None => (spec, None),
+        };
+
+        let mut options = Options::default();
+        let (start_field, start_char) =
+            parse_position(start, &mut options, false).ok_or_else(invalid)?;
+        if start_field == 0 || start_char == Some(0) {
+            return Err(invalid());
+        }
+        let end = match end {
+            Some(end) => {
+                let (field, char) = parse_position(end, &mut options, true).ok_or_else(invalid)?;
+                if field == 0 {
+                    return Err(invalid());
+                }
+                Some((field - 1, char.unwrap_or(0)))
+            }
+            None => None,
+        };
+
+        Ok(Key {
+            start_field: start_field - 1,
+            start_char: start_char.unwrap_or(1) - 1,
+            end,
+            options: match options == Options::default() {
+                true => global,
+                false => options,
+            },
+        })
+    }
+
+    /// The part of `line` the key covers, with fields split on `separator`
+    /// or else where blanks start.
+    pub fn extract<'a>(&self, line: &'a [u8], separator: Option<u8>) -> &'a [u8] {
+        let start = self.start(line, separator);
+        let end = match self.end {
+            Some((field, char)) => self.end(line, separator, field, char),
+            None => line.len(),
+        };
+        &line[start..end.max(start)]
+    }
+
+    fn start(&self, line: &[u8], separator: Option<u8>) -> usize {
+        let mut pos = skip_fields(line, separator, self.start_field);
+        if self.options.skip_start_blanks {
+            pos = skip_blanks(line, pos);
+        }
+        (pos + self.start_char).min(line.len())
+    }
+
+    fn end(&self, line: &[u8], separator: Option<u8>, field: usize, char: usize) -> usize {
+        if char == 0 {
+            // The whole of the last field, without the separator after it
+            let pos = skip_fields(line, separator, field);
+            return match separator {
+                Some(sep) => find(line, pos, |b| b == sep),
+                None => find(line, skip_blanks(line, pos), is_blank),
+            };
+        }
+        let mut pos = skip_fields(line, separator, field);
+        if self.options.skip_end_blanks {
+            pos = skip_blanks(line, pos);
+        }
+        (pos + char).min(line.len())
+    }
+
+    /// Compares the keys of two lines.
+    pub fn compare(&self, a: &[u8], b: &[u8], separator: Option<u8>) -> Ordering {
+        let (a, b) = (self.extract(a, separator), self.extract(b, separator));
+        let ordering = match self.options.kind {
+            Kind::Text if self.options.fold_case => a
+                .iter()
+                .map(u8::to_ascii_uppercase)
+                .cmp(b.iter().map(u8::to_ascii_uppercase)),
+            Kind::Text => a.cmp(b),
+            Kind::Numeric => compare_numbers(a, b),
+            Kind::Human => compare_human(a, b),
+            Kind::Version => compare_versions(a, b),
+        };
+        match self.options.reverse {
+            true => ordering.reverse(),
+            false => ordering,
+        }
+    }
+}
+
+/// Parses `F[.C][OPTS]`, applying the option letters to `options`.
+fn parse_position(pos: &str, options: &mut Options, end: bool) -> Option<(usize, Option<usize>)> {
+    let digits = |s: &str| s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (field, rest) = pos.split_at(digits(pos));
+    let field = field.parse().ok()?;
+    let (char, rest) = match rest.strip_prefix('.') {
+        Some(rest) => {
+            let (char, rest) = rest.split_at(digits(rest));
+            (Some(char.parse().ok()?), rest)
+        }
+        None => (None, rest),
+    };
+    rest.chars()
+        .all(|c| options.set(c, end))
+        .then_some((field, char))
+}
+
+fn is_blank(b: u8) -> bool {
+    b == b' ' || b == b'\t'
+}
+
+fn find(line: &[u8], from: usize, pred: impl Fn(u8) -> bool) -> usize {
+    line[from..]
+        .iter()
+        .position(|&b| pred(b))
+        .map_or(line.len(), |i| from + i)
+}
+
+fn skip_blanks(line: &[u8], from: usize) -> usize {
+    find(line, from, |b| !is_blank(b))
+}
+
+/// The position after `count` fields. Without a separator a field is a
+/// run of blanks followed by a run of anything else, so the blanks
+/// before a field belong to it.
+fn skip_fields(line: &[u8], separator: Option<u8>, count: usize) -> usize {
+    let mut pos = 0;
+    for _ in 0..count {
+        if pos == line.len() {
+            break;
+        }
+        pos = match separator {
+            Some(sep) => (find(line, pos, |b| b == sep) + 1).min(line.len()),
+            None => find(line, skip_blanks(line, pos), is_blank),
+        };
+    }
+    pos
+}
+
+/// A number as -n reads it: the sign, the integer digits without leading
+/// zeros and the fraction digits without trailing zeros, followed by
+/// whatever comes after it. Zero is never negative.
+fn parse_number(text: &[u8]) -> (bool, &[u8], &[u8], &[u8]) {
+    let text = &text[skip_blanks(text, 0)..];
+    let (negative, text) = match text.strip_prefix(b"-") {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let digits = |s: &[u8]| {
+        s.iter()
+            .position(|b| !b.is_ascii_digit())
+            .unwrap_or(s.len())
+    };
+    let (int, mut rest) = text.split_at(digits(text));
+    let int = &int[int.iter().position(|&b| b != b'0').unwrap_or(int.len())..];
+    let mut frac: &[u8] = &[];
+    if let Some(after_point) = rest.strip_prefix(b".") {
+        let (digits, after) = after_point.split_at(digits(after_point));
+        frac = &digits[..digits.iter().rposition(|&b| b != b'0').map_or(0, |i| i + 1)];
+        rest = after;
+    }
+    let zero = int.is_empty() && frac.is_empty();
+    (negative && !zero, int, frac, rest)
+}
+
+/// Compares two numbers digit by digit, so they may be of any length. Text
+/// that is not a number counts as zero.
+fn compare_numbers(a: &[u8], b: &[u8]) -> Ordering {
+    let (a_negative, a_int, a_frac, _) = parse_number(a);
+    let (b_negative, b_int, b_frac, _) = parse_number(b);
+    let magnitude = a_int
+        .len()
+        .cmp(&b_int.len())
+        .then_with(|| a_int.cmp(b_int))
+        .then_with(|| a_frac.cmp(b_frac));
+    match (a_negative, b_negative) {
+        (false, false) => magnitude,
+        (true, true) => magnitude.reverse(),
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+    }
+}
+
+/// Compares numbers like 2K and 1G: by sign and suffix first, then by
+/// the number, so 1G sorts after 2000M.
+fn compare_human(a: &[u8], b: &[u8]) -> Ordering {
+    let unit_order = |text: &[u8]| {
+        let (negative, int, frac, rest) = parse_number(text);
+        // Zero is zero, whatever its suffix
+        if int.is_empty() && frac.is_empty() {
+            return 0;
+        }
+        let order = match rest.first() {
+            Some(b'k' | b'K') => 1,
+            Some(b'M') => 2,
+            Some(b'G') => 3,
+            Some(b'T') => 4,
+            Some(b'P') => 5,
+            Some(b'E') => 6,
+            Some(b'Z') => 7,
+            Some(b'Y') => 8,
+            Some(b'R') => 9,
+            Some(b'Q') => 10,
+            _ => 0,
+        };
+        match negative {
+            true => -order,
+            false => order,
+        }
+    };
+    unit_order(a)
+        .cmp(&unit_order(b))
+        .then_with(|| compare_numbers(a, b))
+}
+
+/// Compares like GNU's filevercmp: names starting with "." come first,
+/// and file suffixes such as ".tar.gz" only break ties between the rest.
+fn compare_versions(a: &[u8], b: &[u8]) -> Ordering {
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Less,
+        (false, true) => return Ordering::Greater,
+        (false, false) => {}
+    }
+    let (mut a, mut b) = (a, b);
+    match (a[0] == b'.', b[0] == b'.') {
+        (true, true) => {
+            // "." first, then "..", then other hidden names
+            for special in [&b"."[..], b".."] {
+                match (a == special, b == special) {
+                    (true, true) => return Ordering::Equal,
+                    (true, false) => return Ordering::Less,
+                    (false, true) => return Ordering::Greater,
+                    (false, false) => {}
+                }
+            }
+            (a, b) = (&a[1..], &b[1..]);
+        }
+        (true, false) => return Ordering::Less,
+        (false, true) => return Ordering::Greater,
+        (false, false) => {}
+    }
+
+    let (a_prefix, b_prefix) = (without_suffix(a), without_suffix(b));
+    match compare_version_parts(a_prefix, b_prefix) {
+        Ordering::Equal if a_prefix.len() != a.len() || b_prefix.len() != b.len() => {
+            compare_version_parts(a, b)
+        }
+        ordering => ordering,
+    }
+}
+
+/// `name` without its file suffix: the trailing run of "." followed by a
+/// letter or "~" and then letters, digits or "~".
+fn without_suffix(name: &[u8]) -> &[u8] {
+    let mut prefix_len = 0;
+    let mut i = 0;
+    while i < name.len() {
+        i += 1;
+        prefix_len = i;
+        while i + 1 < name.len()
+            && name[i] == b'.'
+            && (name[i + 1].is_ascii_alphabetic() || name[i + 1] == b'~')
+        {
+            i += 2;
+            while i < name.len() && (name[i].is_ascii_alphanumeric() || name[i] == b'~') {
+                i += 1;
+            }
+        }
+    }
+    &name[..prefix_len]
+}
+
+/// Compares alternating runs of non-digits and digits: digits by value,
+/// other bytes with letters before everything else except "~", which
+/// comes even before the end of the text.
+fn compare_version_parts(a: &[u8], b: &[u8]) -> Ordering {
+    let order = |text: &[u8], i: usize| -> i32 {
+        match text.get(i) {
+            None => -1,
+            Some(c) if c.is_ascii_digit() => 0,
+            Some(c) if c.is_ascii_alphabetic() => *c as i32,
+            Some(b'~') => -2,
+            Some(c) => *c as i32 + 256,
+        }
+    };
+    let is_digit = |text: &[u8], i: usize| text.get(i).is_some_and(u8::is_ascii_digit);
+
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        while (i < a.len() && !is_digit(a, i)) || (j < b.len() && !is_digit(b, j)) {
+            let (a_order, b_order) = (order(a, i), order(b, j));
+            if a_order != b_order {
+                return a_order.cmp(&b_order);
+            }
+            i += 1;
+            j += 1;
+        }
+        while a.get(i) == Some(&b'0') {
+            i += 1;
+        }
+        while b.get(j) == Some(&b'0') {
+            j += 1;
+        }
+        let mut first_difference = Ordering::Equal;
+        while is_digit(a, i) && is_digit(b, j) {
+            first_difference = first_difference.then(a[i].cmp(&b[j]));
+            i += 1;
+            j += 1;
+        }
+        if is_digit(a, i) {
+            return Ordering::Greater;
+        }
+        if is_digit(b, j) {
+            return Ordering::Less;
+        }
+        if first_difference != Ordering::Equal {
+            return first_difference;
+        }
+    }
+    Ordering::Equal
+}
+
+#[test]
+fn test_parse() {
+    let global = Options {
+        fold_case: true,
+        ..Options::default()
+    };
+    assert_eq!(
+        Key::parse("2", global),
+        Ok(Key {
+            start_field: 1,
+            start_char: 0,
+            end: None,
+            options: global,
+        })
+    );
+    let key = Key::parse("1.3b,2.4nr", global).unwrap();
+    assert_eq!(
+        (key.start_field, key.start_char, key.end),
+        (0, 2, Some((1, 4)))
+    );
+    assert_eq!(
+        key.options,
+        Options {
+            kind: Kind::Numeric,
+            skip_start_blanks: true,
+            reverse: true,
+            ..Options::default()
+        }
+    );
+    assert!(Key::parse("3,3b", global).unwrap().options.skip_end_blanks);
+    for bad in ["", "0", "1.0", "1,0", "x", "1.", "1nV", "1,2q", "1,,2"] {
+        assert_eq!(Key::parse(bad, global), Err(bad.to_string()));
+    }
+}
+
+#[test]
+fn test_extract() {
+    let key = |spec: &str| Key::parse(spec, Options::default()).unwrap();
+    let line = b"  one two\tthree";
+    assert_eq!(key("1").extract(line, None), line);
+    assert_eq!(key("2").extract(line, None), b" two\tthree");
+    assert_eq!(key("2,2").extract(line, None), b" two");
+    assert_eq!(key("2b,2").extract(line, None), b"two");
+    assert_eq!(key("1.3,1.4").extract(line, None), b"on");
+    assert_eq!(key("1.2b,2.2").extract(line, None), b"ne t");
+    assert_eq!(key("4").extract(line, None), b"");
+    assert_eq!(key("2,1").extract(line, None), b"");
+
+    let line = b"a:b::d";
+    assert_eq!(key("2,2").extract(line, Some(b':')), b"b");
+    assert_eq!(key("3,3").extract(line, Some(b':')), b"");
+    assert_eq!(key("3").extract(line, Some(b':')), b":d");
+    assert_eq!(key("4.1,4.1").extract(line, Some(b':')), b"d");
+    assert_eq!(key("5").extract(line, Some(b':')), b"");
+}
+
+#[test]
+fn test_compare_numbers() {
+    let sorted = [
+        "-1000",
+        "-20.5",
+        "-20",
+        "-3",
+        "-0.5",
+        "",
+        "x",
+        "0",
+        "-0",
+        "0.0",
+        "0.001",
+        "1",
+        "01.50",
+        "2",
+        "10",
+        "123456789012345678901234567890",
+    ];
+    for pair in sorted.windows(2) {
+        let (a, b) = (pair[0].as_bytes(), pair[1].as_bytes());
+        assert_ne!(compare_numbers(a, b), Ordering::Greater, "{:?}", pair);
+        assert_ne!(compare_numbers(b, a), Ordering::Less, "{:?}", pair);
+    }
+    assert_eq!(compare_numbers(b"  1.5x", b"1.50"), Ordering::Equal);
+    assert_eq!(compare_numbers(b"-0", b"0"), Ordering::Equal);
+}
+
+#[test]
+fn test_compare_human() {
+    let sorted = [
+        "-1G", "-2K", "-1", "0", "0M", "1", "1000", "1K", "2K", "1.5M", "1G",
+    ];
+    for pair in sorted.windows(2) {
+        let (a, b) = (pair[0].as_bytes(), pair[1].as_bytes());
+        assert_ne!(compare_human(a, b), Ordering::Greater, "{:?}", pair);
+        assert_ne!(compare_human(b, a), Ordering::Less, "{:?}", pair);
+    }
+}
+
+#[test]
+fn test_compare_versions() {
+    let sorted = [
+        "",
+        ".",
+        "..",
+        ".hidden",
+        "1.2~rc1",
+        "1.2",
+        "1.2.1",
+        "1.10",
+        "a",
+        "a1",
+        "a2",
+        "a10",
+        "a10b",
+        "b~",
+        "b",
+        "b+",
+        "foo-1.2.tar.gz",
+        "foo-1.10.tar.gz",
+    ];
+    for pair in sorted.windows(2) {
+        let (a, b) = (pair[0].as_bytes(), pair[1].as_bytes());
+        assert_eq!(compare_versions(a, b), Ordering::Less, "{:?}", pair);
+        assert_eq!(compare_versions(b, a), Ordering::Greater, "{:?}", pair);
+    }
+    assert_eq!(compare_versions(b"a01", b"a1"), Ordering::Equal);
+}
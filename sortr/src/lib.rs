@@ -0,0 +1,304 @@
+mod key;
+mod merge;
+
+use clap::{Arg, ArgAction, Command};
+use common::{flag, open, parse_size, Error, MyResult};
+use key::{Key, Kind, Options};
+use merge::Sorter;
+use std::cmp::Ordering;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::PathBuf;
+
+/// Bytes of input held in memory before sorted runs are spilled to
+/// temporary files, unless -S says otherwise.
+const DEFAULT_BUFFER_SIZE: usize = 64 * 1024 * 1024;
+
+#[derive(Debug)]
+pub struct Config {
+    files: Vec<String>,
+    out_file: Option<String>,
+    compare: Comparator,
+    buffer_size: usize,
+    temp_dir: PathBuf,
+}
+
+/// The order lines are sorted in.
+#[derive(Debug)]
+struct Comparator {
+    /// The -k keys, or the whole line when none were given.
+    keys: Vec<Key>,
+    separator: Option<u8>,
+    /// -u: keep only the first of lines whose keys compare equal.
+    unique: bool,
+    /// -s: leave lines whose keys compare equal in input order rather than
+    /// comparing them byte by byte.
+    stable: bool,
+    /// -r, which also reverses the byte-by-byte comparison.
+    reverse: bool,
+}
+
+impl Comparator {
+    /// Compares the keys of two lines in turn.
+    fn compare_keys(&self, a: &[u8], b: &[u8]) -> Ordering {
+        self.keys
+            .iter()
+            .map(|key| key.compare(a, b, self.separator))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+
+    /// Compares two lines by their keys and, as a last resort unless -s or
+    /// -u is given, byte by byte.
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        let ordering = self.compare_keys(a, b);
+        if ordering.is_ne() || self.stable || self.unique {
+            return ordering;
+        }
+        match self.reverse {
+            true => b.cmp(a),
+            false => a.cmp(b),
+        }
+    }
+}
+
+pub fn run(config: Config) -> MyResult<()> {
+    let mut sorter = Sorter::new(&config.compare, config.buffer_size, &config.temp_dir);
+    for filename in &config.files {
+        let mut file = open(filename).map_err(|e| e.in_file(filename))?;
+        loop {
+            let mut line = Vec::new();
+            if file
+                .read_until(b'\n', &mut line)
+                .map_err(|e| Error::from(e).in_file(filename))?
+                == 0
+            {
+                break;
+            }
+            if line.last() == Some(&b'\n') {
+                line.pop();
+            }
+            sorter.push(line)?;
+        }
+    }
+
+    // Only opened now that the input has been read, so it may be one of
+    // the input files
+    let mut out: Box<dyn Write> = match config.out_file.as_deref() {
+        None | Some("-") => Box::new(BufWriter::new(io::stdout())),
+        Some(name) => Box::new(BufWriter::new(
+            File::create(name).map_err(|e| Error::from(e).in_file(name))?,
+        )),
+    };
+    sorter.finish(&mut out)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Parses a -S size. Unlike other sizes a bare number is in kibibytes and
+/// `b` means bytes.
+fn parse_buffer_size(val: &str) -> MyResult<usize> {
+    let bytes =
+        match val.strip_suffix('b') {
+            Some(bytes) => parse_size(bytes)?,
+            None if val.ends_with(|c: char| c.is_ascii_digit()) => parse_size(val)?
+                .checked_mul(1024)
+                .ok_or_else(|| Error::Usage(val.to_string()))?,
+            None => parse_size(val)?,
+        };
+    Ok(usize::try_from(bytes).unwrap_or(usize::MAX))
+}
+
+pub fn get_args() -> MyResult<Config> {
+    get_args_from(std::env::args_os())
+}
+
+/// Like `get_args`, but parses `args` (whose first item is the program name)
+/// instead of the process arguments.
+pub fn get_args_from<I, T>(args: I) -> MyResult<Config>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let mut matches = Command::new("sortr")
+        .version("0.1.0")
+        .author("Derek Warner <derekw3@illinois.edu>")
+        .about("A rusty sort")
+        .arg(
+            Arg::new("files")
+                .value_name("FILE")
+                .help("Input file(s)")
+                .num_args(0..)
+                .default_value("-"),
+        )
+        .arg(
+            Arg::new("numeric")
+                .short('n')
+                .long("numeric-sort")
+                .help("Compare by numeric value")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("human")
+                .short('h')
+                .long("human-numeric-sort")
+                .help("Compare numbers with SI suffixes, e.g. 2K or 1G")
+                .num_args(0)
+                .conflicts_with("numeric"),
+        )
+        .arg(
+            Arg::new("version")
+                .short('V')
+                .long("version-sort")
+                .help("Compare version numbers within the text")
+                .num_args(0)
+                .conflicts_with_all(["numeric", "human"]),
+        )
+        .arg(
+            Arg::new("ignore_case")
+                .short('f')
+                .long("ignore-case")
+                .help("Fold lowercase to uppercase when comparing")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("reverse")
+                .short('r')
+                .long("reverse")
+                .help("Reverse the result of comparisons")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("unique")
+                .short('u')
+                .long("unique")
+                .help("Output only the first of lines that compare equal")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("stable")
+                .short('s')
+                .long("stable")
+                .help("Keep lines with equal keys in input order")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("key")
+                .short('k')
+                .long("key")
+                .value_name("KEYDEF")
+                .help("Sort by a key, given as F[.C][OPTS][,F[.C][OPTS]]")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("separator")
+                .short('t')
+                .long("field-separator")
+                .value_name("SEP")
+                .help("Use SEP instead of blanks to separate fields")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("out_file")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Write the output to FILE instead of stdout")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("buffer_size")
+                .short('S')
+                .long("buffer-size")
+                .value_name("SIZE")
+                .help("Hold SIZE of input in memory before using temporary files")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("temp_dir")
+                .short('T')
+                .long("temporary-directory")
+                .value_name("DIR")
+                .help("Put temporary files in DIR")
+                .num_args(1),
+        )
+        .disable_help_flag(true)
+        .disable_version_flag(true)
+        .arg(
+            Arg::new("help")
+                .long("help")
+                .help("Print help")
+                .action(ArgAction::Help),
+        )
+        .arg(
+            Arg::new("print_version")
+                .long("version")
+                .help("Print version")
+                .action(ArgAction::Version),
+        )
+        .get_matches_from(args);
+
+    let mut global = Options {
+        fold_case: flag(&matches, "ignore_case"),
+        reverse: flag(&matches, "reverse"),
+        ..Options::default()
+    };
+    if flag(&matches, "numeric") {
+        global.kind = Kind::Numeric;
+    } else if flag(&matches, "human") {
+        global.kind = Kind::Human;
+    } else if flag(&matches, "version") {
+        global.kind = Kind::Version;
+    }
+
+    let mut keys = Vec::new();
+    for spec in matches.remove_many::<String>("key").into_iter().flatten() {
+        keys.push(
+            Key::parse(&spec, global).map_err(|e| Error::Usage(format!("illegal key -- {}", e)))?,
+        );
+    }
+    if keys.is_empty() {
+        keys.push(Key::line(global));
+    }
+
+    let separator = match matches.remove_one::<String>("separator") {
+        Some(sep) if sep.len() == 1 => Some(sep.as_bytes()[0]),
+        Some(sep) => return Err(Error::Usage(format!("illegal separator -- {}", sep))),
+        None => None,
+    };
+
+    let buffer_size = match matches.remove_one::<String>("buffer_size") {
+        Some(size) => parse_buffer_size(&size)
+            .map_err(|e| Error::Usage(format!("illegal buffer size -- {}", e)))?,
+        None => DEFAULT_BUFFER_SIZE,
+    };
+
+    Ok(Config {
+        files: matches.remove_many("files").unwrap().collect(),
+        out_file: matches.remove_one("out_file"),
+        compare: Comparator {
+            keys,
+            separator,
+            unique: flag(&matches, "unique"),
+            stable: flag(&matches, "stable"),
+            reverse: global.reverse,
+        },
+        buffer_size,
+        temp_dir: matches
+            .remove_one::<String>("temp_dir")
+            .map_or_else(std::env::temp_dir, PathBuf::from),
+    })
+}
+
+#[test]
+fn test_parse_buffer_size() {
+    assert_eq!(parse_buffer_size("100b").unwrap(), 100);
+    assert_eq!(parse_buffer_size("2").unwrap(), 2048);
+    assert_eq!(parse_buffer_size("2K").unwrap(), 2048);
+    assert_eq!(parse_buffer_size("1M").unwrap(), 1024 * 1024);
+    assert_eq!(parse_buffer_size("1kB").unwrap(), 1000);
+    for bad in ["", "b", "x", "1x", "-1"] {
+        assert!(parse_buffer_size(bad).is_err(), "{}", bad);
+    }
+}
@@ -0,0 +1,6 @@
+fn main() {
+    if let Err(e) = sortr::get_args().and_then(sortr::run) {
+        eprintln!("{}", e);
+        std::process::exit(e.exit_code());
+    }
+}
@@ -0,0 +1,226 @@
+//! External merge sort. Lines are collected until they outgrow the buffer
+//! size (-S), then sorted and spilled to an unnamed temporary file as a
+//! sorted run. As soon as the newest `MERGE_WIDTH` runs have all been
+//! merged as often (they are at the same level), they are merged into one
+//! run a level up, so every level holds fewer than `MERGE_WIDTH` runs and
+//! the open files grow only with the logarithm of the input size, while
+//! each line is rewritten once per level. At the end the runs, plus
+//! whatever is still in memory, are merged into the output. Only adjacent
+//! runs are merged, in the order they were written, and ties go to the
+//! earlier run, so the sort stays stable.
+
+use crate::Comparator;
+use common::{Error, MyResult};
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::mem;
+use std::path::Path;
+
+/// The most runs merged at once, as with GNU sort's default --batch-size.
+const MERGE_WIDTH: usize = 16;
+
+/// What a line costs beyond its bytes while held in memory.
+const LINE_OVERHEAD: usize = mem::size_of::<Vec<u8>>();
+
+pub struct Sorter<'a> {
+    compare: &'a Comparator,
+    /// Bytes of lines to hold before spilling them.
+    buffer_size: usize,
+    temp_dir: &'a Path,
+    lines: Vec<Vec<u8>>,
+    /// Bytes held in `lines`, counting `LINE_OVERHEAD` for each.
+    held: usize,
+    /// Spilled runs, oldest first, each rewound to its start, with the
+    /// number of merges that went into them.
+    runs: Vec<(File, usize)>,
+}
+
+impl<'a> Sorter<'a> {
+    pub fn new(compare: &'a Comparator, buffer_size: usize, temp_dir: &'a Path) -> Sorter<'a> {
+        Sorter {
+            compare,
+            buffer_size,
+            temp_dir,
+            lines: Vec::new(),
+            held: 0,
+            runs: Vec::new(),
+        }
+    }
+
+    /// Adds a line, without its newline, spilling what is held when the
+    /// buffer is full.
+    pub fn push(&mut self, line: Vec<u8>) -> MyResult<()> {
+        self.held += line.len() + LINE_OVERHEAD;
+        self.lines.push(line);
+        if self.held >= self.buffer_size {
+            self.sort_lines();
+            let lines = mem::take(&mut self.lines);
+            let run =
+                self.new_run(|out| lines.iter().try_for_each(|line| write_line(out, line)))?;
+            self.runs.push((run, 0));
+            self.held = 0;
+            self.merge_level()?;
+        }
+        Ok(())
+    }
+
+    /// Merges the newest `MERGE_WIDTH` runs into one while they are all at
+    /// the same level.
+    fn merge_level(&mut self) -> MyResult<()> {
+        while self.runs.len() >= MERGE_WIDTH {
+            let newest = self.runs.len() - MERGE_WIDTH;
+            let level = self.runs[newest].1;
+            if self.runs[newest..].iter().any(|(_, l)| *l != level) {
+                break;
+            }
+            let batch = runs(self.runs.drain(newest..).map(|(file, _)| file));
+            let run = self.new_run(|out| merge(self.compare, batch, out))?;
+            self.runs.push((run, level + 1));
+        }
+        Ok(())
+    }
+
+    /// Writes every line pushed, in order, to `out`.
+    pub fn finish(mut self, out: &mut impl Write) -> MyResult<()> {
+        self.sort_lines();
+        // Leave room for the lines in memory in the last merge
+        while self.runs.len() >= MERGE_WIDTH {
+            let oldest = runs(self.runs.drain(..MERGE_WIDTH).map(|(file, _)| file));
+            let run = self.new_run(|out| merge(self.compare, oldest, out))?;
+            self.runs.insert(0, (run, 0));
+        }
+
+        let mut all = runs(self.runs.drain(..).map(|(file, _)| file));
+        all.push(Run::Memory(mem::take(&mut self.lines).into_iter()));
+        merge(self.compare, all, out)?;
+        Ok(())
+    }
+
+    /// Sorts the lines held, dropping all but the first of equal lines
+    /// with -u.
+    fn sort_lines(&mut self) {
+        let compare = self.compare;
+        self.lines.sort_by(|a, b| compare.compare(a, b));
+        if compare.unique {
+            self.lines
+                .dedup_by(|line, kept| compare.compare_keys(kept, line) == Ordering::Equal);
+        }
+    }
+
+    /// Writes a run to a new temporary file, returned rewound.
+    fn new_run(
+        &self,
+        write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+    ) -> MyResult<File> {
+        let temp_error = |source| Error::File {
+            path: self.temp_dir.display().to_string(),
+            source,
+        };
+        let mut out = BufWriter::new(tempfile::tempfile_in(self.temp_dir).map_err(temp_error)?);
+        write(&mut out).map_err(temp_error)?;
+        let mut file = out.into_inner().map_err(|e| temp_error(e.into_error()))?;
+        file.seek(SeekFrom::Start(0)).map_err(temp_error)?;
+        Ok(file)
+    }
+}
+
+fn write_line(out: &mut impl Write, line: &[u8]) -> io::Result<()> {
+    out.write_all(line)?;
+    out.write_all(b"\n")
+}
+
+/// A sorted run being merged.
+enum Run {
+    File(BufReader<File>),
+    Memory(std::vec::IntoIter<Vec<u8>>),
+}
+
+impl Run {
+    fn next_line(&mut self) -> io::Result<Option<Vec<u8>>> {
+        match self {
+            Run::File(file) => {
+                let mut line = Vec::new();
+                if file.read_until(b'\n', &mut line)? == 0 {
+                    return Ok(None);
+                }
+                line.pop();
+                Ok(Some(line))
+            }
+            Run::Memory(lines) => Ok(lines.next()),
+        }
+    }
+}
+
+fn runs(files: impl Iterator<Item = File>) -> Vec<Run> {
+    files.map(|file| Run::File(BufReader::new(file))).collect()
+}
+
+/// Merges sorted `runs` into `out`. With -u a line equal to the one
+/// before it is dropped, which keeps the first of each equal group since
+/// ties go to the earliest run.
+fn merge(compare: &Comparator, mut runs: Vec<Run>, out: &mut impl Write) -> io::Result<()> {
+    let mut heads = Vec::with_capacity(runs.len());
+    for run in &mut runs {
+        heads.push(run.next_line()?);
+    }
+    let mut last: Option<Vec<u8>> = None;
+    loop {
+        let mut smallest: Option<(usize, &Vec<u8>)> = None;
+        for (i, head) in heads.iter().enumerate() {
+            if let Some(line) = head {
+                if smallest.is_none_or(|(_, min)| compare.compare(line, min) == Ordering::Less) {
+                    smallest = Some((i, line));
+                }
+            }
+        }
+        let Some((i, _)) = smallest else {
+            return Ok(());
+        };
+
+        let line = mem::replace(&mut heads[i], runs[i].next_line()?).unwrap();
+        let repeated = last
+            .as_ref()
+            .is_some_and(|last| compare.compare_keys(last, &line) == Ordering::Equal);
+        if !repeated {
+            write_line(out, &line)?;
+        }
+        if compare.unique {
+            last = Some(line);
+        }
+    }
+}
+
+#[test]
+fn test_open_runs_bounded() {
+    use crate::key::{Key, Options};
+
+    let compare = Comparator {
+        keys: vec![Key::line(Options::default())],
+        separator: None,
+        unique: false,
+        stable: false,
+        reverse: false,
+    };
+    let temp_dir = std::env::temp_dir();
+    // Every line is a run of its own
+    let mut sorter = Sorter::new(&compare, 1, &temp_dir);
+    let lines: Vec<Vec<u8>> = (0..5000)
+        .map(|i| format!("{:04}", (i * 7919) % 5000).into_bytes())
+        .collect();
+    for line in &lines {
+        sorter.push(line.clone()).unwrap();
+        // 5000 runs take four levels of fewer than MERGE_WIDTH each
+        assert!(sorter.runs.len() < 4 * MERGE_WIDTH);
+    }
+
+    let mut out = Vec::new();
+    sorter.finish(&mut out).unwrap();
+    let mut expected = lines;
+    expected.sort();
+    let expected: Vec<u8> = expected
+        .iter()
+        .flat_map(|line| [&line[..], b"\n"].concat())
+        .collect();
+    assert_eq!(out, expected);
+}
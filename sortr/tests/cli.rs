@@ -0,0 +1,497 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use rand::{distributions::Alphanumeric, Rng};
+use std::{error::Error, fs};
+use tempfile::TempDir;
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+const PRG: &str = "sortr";
+const EMPTY: &str = "tests/inputs/empty.txt";
+const WORDS: &str = "tests/inputs/words.txt";
+const NUMBERS: &str = "tests/inputs/numbers.txt";
+const SIZES: &str = "tests/inputs/sizes.txt";
+const VERSIONS: &str = "tests/inputs/versions.txt";
+const PASSWD: &str = "tests/inputs/passwd.txt";
+const PEOPLE: &str = "tests/inputs/people.txt";
+const NOEOL: &str = "tests/inputs/noeol.txt";
+const LINES: &str = "tests/inputs/lines.txt";
+
+// --------------------------------------------------
+fn random_string() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(7)
+        .map(char::from)
+        .collect()
+}
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename = random_string();
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+fn dies(args: &[&str], expected: &str) -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(expected));
+
+    Ok(())
+}
+
+#[test]
+fn dies_bad_key() -> TestResult {
+    let bad = format!("1{}", random_string());
+    dies(&["-k", &bad, WORDS], &format!("illegal key -- {}", bad))
+}
+
+#[test]
+fn dies_zero_field() -> TestResult {
+    dies(&["-k", "0", WORDS], "illegal key -- 0")
+}
+
+#[test]
+fn dies_incompatible_key_options() -> TestResult {
+    dies(&["-k", "1nV", WORDS], "illegal key -- 1nV")
+}
+
+#[test]
+fn dies_incompatible_options() -> TestResult {
+    dies(&["-n", "-h", WORDS], "cannot be used with")
+}
+
+#[test]
+fn dies_bad_separator() -> TestResult {
+    dies(&["-t", "::", WORDS], "illegal separator -- ::")
+}
+
+#[test]
+fn dies_bad_buffer_size() -> TestResult {
+    let bad = random_string();
+    dies(
+        &["-S", &bad, WORDS],
+        &format!("illegal buffer size -- {}", bad),
+    )
+}
+
+#[test]
+fn dies_bad_file() -> TestResult {
+    let bad = gen_bad_file();
+    let expected = format!("{}: .* [(]os error 2[)]", bad);
+    Command::cargo_bin(PRG)?
+        .args([WORDS, &bad])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(predicate::str::is_match(expected)?);
+
+    Ok(())
+}
+
+#[test]
+fn dies_bad_temp_dir() -> TestResult {
+    let bad = gen_bad_file();
+    let expected = format!("{}: .* [(]os error 2[)]", bad);
+    Command::cargo_bin(PRG)?
+        .args(["-S", "100b", "-T", &bad, WORDS])
+        .assert()
+        .failure()
+        .stderr(predicate::str::is_match(expected)?);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> TestResult {
+    let expected = fs::read(expected_file)?;
+
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run_stdin(args: &[&str], input_file: &str, expected_file: &str) -> TestResult {
+    let expected = fs::read(expected_file)?;
+    let input = fs::read(input_file)?;
+
+    Command::cargo_bin(PRG)?
+        .write_stdin(input)
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+/// Runs with a buffer of a few lines, so the input is spilled to
+/// hundreds of temporary files and merged in several rounds.
+fn run_spilled(args: &[&str], expected_file: &str) -> TestResult {
+    let expected = fs::read(expected_file)?;
+    let dir = TempDir::new()?;
+
+    Command::cargo_bin(PRG)?
+        .args(["-S", "200b", "-T"])
+        .arg(dir.path())
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+    // Nothing is left behind in the temporary directory
+    assert_eq!(fs::read_dir(dir.path())?.count(), 0);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(unix)]
+#[test]
+fn spills_within_file_limit() -> TestResult {
+    // A run per line is thousands of runs; they must not all stay open
+    let expected = fs::read("tests/expected/lines.txt.out")?;
+    let sortr = assert_cmd::cargo::cargo_bin(PRG);
+    Command::new("sh")
+        .arg("-c")
+        .arg(r#"ulimit -n 64 && exec "$0" -S 1b "$1""#)
+        .arg(sortr)
+        .arg(LINES)
+        .assert()
+        .success()
+        .stdout(expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn output_file() -> TestResult {
+    let expected = fs::read("tests/expected/numbers.txt.n.out")?;
+    let dir = TempDir::new()?;
+    let out = dir.path().join("out.txt");
+
+    Command::cargo_bin(PRG)?
+        .args(["-n", NUMBERS, "-o"])
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout("");
+    assert_eq!(fs::read(&out)?, expected);
+
+    Ok(())
+}
+
+#[test]
+fn output_file_is_input() -> TestResult {
+    let expected = fs::read("tests/expected/lines.txt.out")?;
+    let dir = TempDir::new()?;
+    let file = dir.path().join("lines.txt");
+    fs::copy(LINES, &file)?;
+
+    Command::cargo_bin(PRG)?
+        .args(["-S", "1K", "-o"])
+        .arg(&file)
+        .arg(&file)
+        .assert()
+        .success();
+    assert_eq!(fs::read(&file)?, expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn empty() -> TestResult {
+    run(&[EMPTY], "tests/expected/empty.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn words() -> TestResult {
+    run(&[WORDS], "tests/expected/words.txt.out")
+}
+
+#[test]
+fn words_stdin() -> TestResult {
+    run_stdin(&[], WORDS, "tests/expected/words.txt.out")
+}
+
+#[test]
+fn words_f() -> TestResult {
+    run(&["-f", WORDS], "tests/expected/words.txt.f.out")
+}
+
+#[test]
+fn words_r() -> TestResult {
+    run(&["-r", WORDS], "tests/expected/words.txt.r.out")
+}
+
+#[test]
+fn words_u() -> TestResult {
+    run(&["-u", WORDS], "tests/expected/words.txt.u.out")
+}
+
+#[test]
+fn words_fu() -> TestResult {
+    run(&["-fu", WORDS], "tests/expected/words.txt.fu.out")
+}
+
+#[test]
+fn words_fs() -> TestResult {
+    run(&["-fs", WORDS], "tests/expected/words.txt.fs.out")
+}
+
+#[test]
+fn words_fr() -> TestResult {
+    run(&["-fr", WORDS], "tests/expected/words.txt.fr.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn numbers_n() -> TestResult {
+    run(&["-n", NUMBERS], "tests/expected/numbers.txt.n.out")
+}
+
+#[test]
+fn numbers_n_stdin() -> TestResult {
+    run_stdin(&["-n"], NUMBERS, "tests/expected/numbers.txt.n.out")
+}
+
+#[test]
+fn numbers_nr() -> TestResult {
+    run(&["-nr", NUMBERS], "tests/expected/numbers.txt.nr.out")
+}
+
+#[test]
+fn numbers_nu() -> TestResult {
+    run(&["-nu", NUMBERS], "tests/expected/numbers.txt.nu.out")
+}
+
+#[test]
+fn numbers_ns() -> TestResult {
+    run(&["-ns", NUMBERS], "tests/expected/numbers.txt.ns.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn sizes_h() -> TestResult {
+    run(&["-h", SIZES], "tests/expected/sizes.txt.h.out")
+}
+
+#[test]
+fn sizes_hr() -> TestResult {
+    run(&["-hr", SIZES], "tests/expected/sizes.txt.hr.out")
+}
+
+#[test]
+fn sizes_k2() -> TestResult {
+    run(&["-k", "2", SIZES], "tests/expected/sizes.txt.k2.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn versions_upper_v() -> TestResult {
+    run(&["-V", VERSIONS], "tests/expected/versions.txt.V.out")
+}
+
+#[test]
+fn versions_upper_vr() -> TestResult {
+    run(&["-Vr", VERSIONS], "tests/expected/versions.txt.Vr.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn passwd_t_k3n() -> TestResult {
+    run(
+        &["-t", ":", "-k", "3n", PASSWD],
+        "tests/expected/passwd.txt.t3n.out",
+    )
+}
+
+#[test]
+fn passwd_t_k3_3() -> TestResult {
+    run(
+        &["-t", ":", "-k", "3,3", PASSWD],
+        "tests/expected/passwd.txt.t3,3.out",
+    )
+}
+
+#[test]
+fn passwd_t_k7_7_k1r() -> TestResult {
+    run(
+        &["-t", ":", "-k", "7,7", "-k", "1,1r", PASSWD],
+        "tests/expected/passwd.txt.t7,7.k1r.out",
+    )
+}
+
+#[test]
+fn passwd_t_k4n_u() -> TestResult {
+    run(
+        &["-t", ":", "-k", "4,4n", "-u", PASSWD],
+        "tests/expected/passwd.txt.t4n.u.out",
+    )
+}
+
+#[test]
+fn passwd_t_k6_chars_s() -> TestResult {
+    run(
+        &["-t", ":", "-k", "6.2,6.4", "-s", PASSWD],
+        "tests/expected/passwd.txt.t6.2,6.4.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn people_k1_1() -> TestResult {
+    run(&["-k", "1,1", PEOPLE], "tests/expected/people.txt.k1,1.out")
+}
+
+#[test]
+fn people_k1b_1() -> TestResult {
+    run(
+        &["-k", "1b,1", PEOPLE],
+        "tests/expected/people.txt.k1b,1.out",
+    )
+}
+
+#[test]
+fn people_k1bf_k2() -> TestResult {
+    run(
+        &["-k", "1b,1f", "-k", "2", PEOPLE],
+        "tests/expected/people.txt.k1b,1f.k2.out",
+    )
+}
+
+#[test]
+fn people_k3n_k4f() -> TestResult {
+    run(
+        &["-k", "3n", "-k", "4f", PEOPLE],
+        "tests/expected/people.txt.k3n.k4.out",
+    )
+}
+
+#[test]
+fn people_k3nr_k1bf() -> TestResult {
+    run(
+        &["-k", "3nr", "-k", "1bf,1", PEOPLE],
+        "tests/expected/people.txt.k3nr.k1bf.out",
+    )
+}
+
+#[test]
+fn people_k3n_s() -> TestResult {
+    run(
+        &["-s", "-k", "3,3n", PEOPLE],
+        "tests/expected/people.txt.k3,3n.s.out",
+    )
+}
+
+#[test]
+fn people_k4_chars() -> TestResult {
+    run(
+        &["-k", "4.2b,4.3", PEOPLE],
+        "tests/expected/people.txt.k4.2b,4.3.out",
+    )
+}
+
+#[test]
+fn people_k2_char_u() -> TestResult {
+    run(
+        &["-k", "2.1b,2.1bf", "-u", PEOPLE],
+        "tests/expected/people.txt.k2.1b,2.1bf.u.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn all() -> TestResult {
+    run(&[WORDS, NOEOL, NUMBERS], "tests/expected/all.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn lines() -> TestResult {
+    run(&[LINES], "tests/expected/lines.txt.out")
+}
+
+#[test]
+fn lines_spilled() -> TestResult {
+    run_spilled(&[LINES], "tests/expected/lines.txt.out")
+}
+
+#[test]
+fn lines_k2n_s() -> TestResult {
+    run(
+        &["-s", "-k", "2n", LINES],
+        "tests/expected/lines.txt.k2n.s.out",
+    )
+}
+
+#[test]
+fn lines_k2n_s_spilled() -> TestResult {
+    run_spilled(
+        &["-s", "-k", "2n", LINES],
+        "tests/expected/lines.txt.k2n.s.out",
+    )
+}
+
+#[test]
+fn lines_k3v_k1f() -> TestResult {
+    run(
+        &["-k", "3V", "-k", "1f", LINES],
+        "tests/expected/lines.txt.k3V.k1f.out",
+    )
+}
+
+#[test]
+fn lines_k3v_k1f_spilled() -> TestResult {
+    run_spilled(
+        &["-k", "3V", "-k", "1f", LINES],
+        "tests/expected/lines.txt.k3V.k1f.out",
+    )
+}
+
+#[test]
+fn lines_k1f_u() -> TestResult {
+    run(
+        &["-u", "-k", "1,1f", LINES],
+        "tests/expected/lines.txt.k1f.u.out",
+    )
+}
+
+#[test]
+fn lines_k1f_u_spilled() -> TestResult {
+    run_spilled(
+        &["-u", "-k", "1,1f", LINES],
+        "tests/expected/lines.txt.k1f.u.out",
+    )
+}
+
+#[test]
+fn lines_k2nr_k3() -> TestResult {
+    run(
+        &["-k", "2,2nr", "-k", "3", LINES],
+        "tests/expected/lines.txt.k2nr.k3.out",
+    )
+}
+
+#[test]
+fn lines_k2nr_k3_spilled() -> TestResult {
+    run_spilled(
+        &["-k", "2,2nr", "-k", "3", LINES],
+        "tests/expected/lines.txt.k2nr.k3.out",
+    )
+}
@@ -0,0 +1,346 @@
+//! -f and -F: printing what is appended to the inputs after the initial
+//! output. Every pass checks each file for new data, truncation and, when
+//! following by name, rotation. Between passes the process sleeps until
+//! inotify reports a change (on Linux) or the sleep interval runs out, so
+//! --pid is checked at least that often and changes inotify cannot see,
+//! e.g. on network filesystems, still show up.
+
+use crate::Config;
+use common::MyResult;
+use std::fs::{self, File, Metadata};
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// What -f keeps following when a file is renamed or replaced.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Follow {
+    /// The open file, wherever it goes (-f, --follow=descriptor).
+    Descriptor,
+    /// Whatever file has the name, reopening it after rotation
+    /// (-F, --follow=name).
+    Name,
+}
+
+/// An input being followed.
+pub struct Followed {
+    name: String,
+    /// `None` while a file followed by name cannot be opened.
+    file: Option<File>,
+    /// Bytes read so far, to tell when the file has been truncated.
+    position: u64,
+    /// Device and inode of `file`, to tell when the name has been given to
+    /// another file.
+    id: Option<(u64, u64)>,
+}
+
+impl Followed {
+    /// Follows `file` from its current position, or waits for `name` to
+    /// appear when it could not be opened.
+    pub fn new(name: &str, file: Option<File>) -> io::Result<Followed> {
+        let mut followed = Followed {
+            name: name.to_string(),
+            file: None,
+            position: 0,
+            id: None,
+        };
+        if let Some(mut file) = file {
+            followed.position = file.stream_position()?;
+            followed.id = file_id(&file.metadata()?);
+            followed.file = Some(file);
+        }
+        Ok(followed)
+    }
+}
+
+/// Prints data as it is added to `files` until the process given with
+/// --pid exits, or forever without it. `current` is the file the initial
+/// output ended with, if it is followed, and `printed` whether any header
+/// or data came before.
+pub fn follow(
+    config: &Config,
+    files: Vec<Followed>,
+    current: Option<usize>,
+    printed: bool,
+    out: &mut impl Write,
+) -> MyResult<()> {
+    let mut follower = Follower {
+        how: config.follow.unwrap_or(Follow::Descriptor),
+        headers: config.verbose || (!config.quiet && config.files.len() > 1),
+        files,
+        current,
+        printed,
+        out,
+    };
+
+    let mut waiter = Waiter::new(config.inotify, follower.how, &follower.files);
+    loop {
+        // Checked before the pass so whatever the process wrote last is
+        // still printed
+        let exited = config.pid.is_some_and(|pid| !process_alive(pid));
+        for i in 0..follower.files.len() {
+            if follower.how == Follow::Name {
+                follower.check_name(i)?;
+            }
+            follower.print_new(i)?;
+        }
+        follower.out.flush()?;
+        if exited {
+            return Ok(());
+        }
+        waiter.wait(config.sleep);
+    }
+}
+
+struct Follower<'a, W: Write> {
+    how: Follow,
+    headers: bool,
+    files: Vec<Followed>,
+    /// The file printed from last, which needs no new header.
+    current: Option<usize>,
+    /// Whether anything has been printed, so headers after the first are
+    /// set apart by a blank line.
+    printed: bool,
+    out: &'a mut W,
+}
+
+impl<W: Write> Follower<'_, W> {
+    /// Prints whatever has been added to file `i` since the last pass,
+    /// starting over from the top when it has been truncated.
+    fn print_new(&mut self, i: usize) -> MyResult<()> {
+        let followed = &mut self.files[i];
+        let Some(file) = &mut followed.file else {
+            return Ok(());
+        };
+        let len = match file.metadata() {
+            Ok(metadata) => metadata.len(),
+            Err(e) => {
+                eprintln!("{}: {}", followed.name, e);
+                followed.file = None;
+                return Ok(());
+            }
+        };
+        if len < followed.position {
+            eprintln!("{}: file truncated", followed.name);
+            file.seek(SeekFrom::Start(0))?;
+            followed.position = 0;
+        }
+        if len == followed.position {
+            return Ok(());
+        }
+
+        if self.headers && self.current != Some(i) {
+            if self.printed {
+                writeln!(self.out)?;
+            }
+            let name = match followed.name.as_str() {
+                "-" => "standard input",
+                name => name,
+            };
+            writeln!(self.out, "==> {} <==", name)?;
+        }
+        match io::copy(file, self.out) {
+            Ok(bytes) => followed.position += bytes,
+            Err(e) => {
+                eprintln!("{}: {}", followed.name, e);
+                followed.file = None;
+            }
+        }
+        self.current = Some(i);
+        self.printed = true;
+        Ok(())
+    }
+
+    /// Reopens file `i` when its name now belongs to another file, after
+    /// printing the rest of the old one.
+    fn check_name(&mut self, i: usize) -> MyResult<()> {
+        let followed = &self.files[i];
+        match fs::metadata(&followed.name) {
+            Ok(metadata) => {
+                if followed.file.is_some() && file_id(&metadata) == followed.id {
+                    return Ok(());
+                }
+                // It may be gone again already; try on the next pass
+                let Ok(file) = File::open(&followed.name) else {
+                    return Ok(());
+                };
+                let replaced = followed.file.is_some();
+                self.print_new(i)?;
+                let followed = &mut self.files[i];
+                match replaced {
+                    true => eprintln!("{}: has been replaced; following new file", followed.name),
+                    false => eprintln!("{}: has appeared; following new file", followed.name),
+                }
+                followed.id = file_id(&file.metadata()?);
+                followed.file = Some(file);
+                followed.position = 0;
+            }
+            Err(e) => {
+                if followed.file.is_some() {
+                    self.print_new(i)?;
+                    let followed = &mut self.files[i];
+                    eprintln!("{}: has become inaccessible: {}", followed.name, e);
+                    followed.file = None;
+                    followed.id = None;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Whether process `pid` is still running.
+#[cfg(unix)]
+fn process_alive(pid: i32) -> bool {
+    // SAFETY: signal 0 is never delivered; kill only checks that the
+    // process exists and may be signalled.
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: i32) -> bool {
+    true
+}
+
+/// Sleeps between passes.
+enum Waiter {
+    #[cfg(target_os = "linux")]
+    Inotify(linux::Inotify),
+    /// Sleeps out the whole interval.
+    Poll,
+}
+
+impl Waiter {
+    /// Watches the files themselves when following descriptors, and the
+    /// directories holding them when following names so that files
+    /// created or renamed there are noticed too. Falls back to polling
+    /// when inotify is disabled or unavailable.
+    fn new(inotify: bool, how: Follow, files: &[Followed]) -> Waiter {
+        let paths = files
+            .iter()
+            .filter(|followed| followed.name != "-")
+            .map(|followed| {
+                let path = Path::new(&followed.name);
+                match how {
+                    Follow::Descriptor => path,
+                    Follow::Name => match path.parent() {
+                        Some(parent) if parent != Path::new("") => parent,
+                        _ => Path::new("."),
+                    },
+                }
+            });
+        #[cfg(target_os = "linux")]
+        if inotify {
+            if let Some(watcher) = linux::Inotify::new(paths) {
+                return Waiter::Inotify(watcher);
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = (inotify, paths);
+        Waiter::Poll
+    }
+
+    fn wait(&mut self, timeout: Duration) {
+        match self {
+            #[cfg(target_os = "linux")]
+            Waiter::Inotify(watcher) => watcher.wait(timeout),
+            Waiter::Poll => std::thread::sleep(timeout),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::ffi::CString;
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+    use std::time::Duration;
+
+    /// Changes that end a wait: data written, truncation, renames,
+    /// deletion and, in watched directories, files created or moved in.
+    const EVENTS: u32 = libc::IN_MODIFY
+        | libc::IN_ATTRIB
+        | libc::IN_CREATE
+        | libc::IN_DELETE
+        | libc::IN_DELETE_SELF
+        | libc::IN_MOVE_SELF
+        | libc::IN_MOVED_FROM
+        | libc::IN_MOVED_TO;
+
+    pub struct Inotify {
+        fd: OwnedFd,
+    }
+
+    impl Inotify {
+        /// Watches every path it can; `None` when inotify cannot be used
+        /// or no path could be watched.
+        pub fn new<'a>(paths: impl Iterator<Item = &'a Path>) -> Option<Inotify> {
+            // SAFETY: no pointers are passed; a non-negative result is a
+            // new descriptor owned by nothing else.
+            let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+            if fd < 0 {
+                return None;
+            }
+            // SAFETY: `fd` was just opened and is owned here alone.
+            let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+            let mut watching = false;
+            for path in paths {
+                let Ok(path) = CString::new(path.as_os_str().as_bytes()) else {
+                    continue;
+                };
+                // SAFETY: `path` is a valid C string for the whole call.
+                let wd = unsafe { libc::inotify_add_watch(fd.as_raw_fd(), path.as_ptr(), EVENTS) };
+                watching |= wd >= 0;
+            }
+            watching.then_some(Inotify { fd })
+        }
+
+        /// Waits for an event or `timeout`, then discards the queued
+        /// events: the next pass checks every file anyway.
+        pub fn wait(&mut self, timeout: Duration) {
+            let mut poll = libc::pollfd {
+                fd: self.fd.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let millis = timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+            // SAFETY: `poll` is a single valid pollfd for the whole call.
+            if unsafe { libc::poll(&mut poll, 1, millis) } <= 0 {
+                return;
+            }
+
+            let mut buffer = [0u8; 4096];
+            loop {
+                // SAFETY: the kernel writes at most `buffer.len()` bytes
+                // into `buffer`.
+                let bytes = unsafe {
+                    libc::read(
+                        self.fd.as_raw_fd(),
+                        buffer.as_mut_ptr().cast(),
+                        buffer.len(),
+                    )
+                };
+                let interrupted =
+                    bytes < 0 && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted;
+                if bytes <= 0 && !interrupted {
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,416 @@
+mod follow;
+
+use clap::{Arg, ArgAction, Command};
+use common::{
+    flag, open_file, parse_size, seekable_range, start_of_last_lines, Error, MyResult, BLOCK_SIZE,
+};
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::time::Duration;
+
+pub use follow::Follow;
+use follow::Followed;
+
+/// How much of each input -n or -c selects.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Count {
+    /// The last N lines or bytes.
+    Last(u64),
+    /// Everything from line or byte N on, counting from one ("+N").
+    From(u64),
+}
+
+#[derive(Debug)]
+pub struct Config {
+    files: Vec<String>,
+    lines: Count,
+    bytes: Option<Count>,
+    quiet: bool,
+    verbose: bool,
+    follow: Option<Follow>,
+    /// Longest wait between checks for new data while following.
+    sleep: Duration,
+    /// Stop following once this process has exited.
+    pid: Option<i32>,
+    /// Whether to wait on inotify rather than just sleep while following.
+    inotify: bool,
+}
+
+pub fn run(config: Config) -> MyResult<()> {
+    let mut stdout = io::stdout().lock();
+    let mut file_count = 0;
+    let num_files = config.files.len();
+    // Like GNU tail, print no headers around nothing when not following
+    let prints_nothing = matches!(
+        (config.bytes, config.lines),
+        (Some(Count::Last(0)), _) | (None, Count::Last(0))
+    ) && config.follow.is_none();
+    let print_headers = !prints_nothing && (config.verbose || (!config.quiet && num_files > 1));
+    let mut followed = Vec::new();
+    let mut current = None;
+    for filename in &config.files {
+        let mut file = match open_file(filename) {
+            Err(e) => {
+                eprintln!("{}: {}", filename, e);
+                // Following by name waits for the file to turn up
+                if config.follow == Some(Follow::Name) && filename != "-" {
+                    followed.push(Followed::new(filename, None)?);
+                }
+                continue;
+            }
+            Ok(file) => file,
+        };
+        if print_headers {
+            if file_count != 0 {
+                writeln!(stdout)?;
+            }
+            let name = match filename.as_str() {
+                "-" => "standard input",
+                name => name,
+            };
+            writeln!(stdout, "==> {} <==", name)?;
+        }
+        match (config.bytes, config.lines) {
+            (Some(Count::Last(bytes)), _) => last_bytes(&mut file, bytes, &mut stdout)?,
+            (Some(Count::From(byte)), _) => from_byte(&mut file, byte, &mut stdout)?,
+            (None, Count::Last(lines)) => last_lines(&mut file, lines, &mut stdout)?,
+            (None, Count::From(line)) => from_line(&mut file, line, &mut stdout)?,
+        }
+        file_count += 1;
+
+        // Pipes have been read to the end, so only files can grow, and
+        // stdin has no name to follow
+        current = None;
+        let followable = match config.follow {
+            Some(Follow::Descriptor) => file.metadata()?.is_file(),
+            Some(Follow::Name) => filename != "-",
+            None => false,
+        };
+        if followable {
+            current = Some(followed.len());
+            followed.push(Followed::new(filename, Some(file))?);
+        }
+    }
+
+    match config.follow {
+        // Only pipes were read, and they have been read to the end
+        Some(_) if followed.is_empty() && file_count > 0 => Ok(()),
+        Some(_) if followed.is_empty() => Err(Error::Usage("no files remaining".to_string())),
+        Some(_) => follow::follow(&config, followed, current, file_count > 0, &mut stdout),
+        None => Ok(()),
+    }
+}
+
+/// Prints the last `count` bytes of `file`.
+fn last_bytes(file: &mut File, count: u64, out: &mut impl Write) -> MyResult<()> {
+    if let Some((start, end)) = seekable_range(file)? {
+        let from = end.saturating_sub(count).max(start);
+        file.seek(SeekFrom::Start(from))?;
+        io::copy(file, out)?;
+        return Ok(());
+    }
+
+    // Keep the most recent `count` bytes, trimming a block at a time
+    let count = usize::try_from(count).unwrap_or(usize::MAX);
+    let mut tail = Vec::new();
+    let mut buffer = vec![0; BLOCK_SIZE];
+    loop {
+        let bytes = file.read(&mut buffer)?;
+        if bytes == 0 {
+            break;
+        }
+        tail.extend_from_slice(&buffer[..bytes]);
+        if tail.len() - count.min(tail.len()) >= BLOCK_SIZE {
+            tail.drain(..tail.len() - count);
+        }
+    }
+    out.write_all(&tail[tail.len() - count.min(tail.len())..])?;
+    Ok(())
+}
+
+/// Prints the last `count` lines of `file`. A final line without a newline
+/// still counts as a line.
+fn last_lines(file: &mut File, count: u64, out: &mut impl Write) -> MyResult<()> {
+    if let Some((start, end)) = seekable_range(file)? {
+        let count = usize::try_from(count).unwrap_or(usize::MAX);
+        let from = start_of_last_lines(file, start, end, count)?;
+        file.seek(SeekFrom::Start(from))?;
+        io::copy(file, out)?;
+        return Ok(());
+    }
+
+    // Keep the most recent `count` lines, reusing the one that falls out
+    let mut file = BufReader::new(file);
+    let mut window: VecDeque<Vec<u8>> = VecDeque::new();
+    let mut line = Vec::new();
+    while file.read_until(b'\n', &mut line)? != 0 {
+        window.push_back(line);
+        line = match window.len() as u64 > count {
+            true => window.pop_front().unwrap(),
+            false => Vec::new(),
+        };
+        line.clear();
+    }
+    for line in window {
+        out.write_all(&line)?;
+    }
+    Ok(())
+}
+
+/// Prints `file` from byte `first` on; +0 is taken as +1.
+fn from_byte(file: &mut File, first: u64, out: &mut impl Write) -> MyResult<()> {
+    let skip = first.saturating_sub(1);
+    if let Some((start, end)) = seekable_range(file)? {
+        file.seek(SeekFrom::Start(start.saturating_add(skip).min(end)))?;
+    } else {
+        io::copy(&mut file.take(skip), &mut io::sink())?;
+    }
+    io::copy(file, out)?;
+    Ok(())
+}
+
+/// Prints `file` from line `first` on; +0 is taken as +1.
+fn from_line(file: &mut File, first: u64, out: &mut impl Write) -> MyResult<()> {
+    let mut file = BufReader::new(file);
+    let mut skipped = 0;
+    while skipped + 1 < first {
+        let buffer = file.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(());
+        }
+        match buffer.iter().position(|&b| b == b'\n') {
+            Some(i) => {
+                file.consume(i + 1);
+                skipped += 1;
+            }
+            None => {
+                let len = buffer.len();
+                file.consume(len);
+            }
+        }
+    }
+    io::copy(&mut file, out)?;
+    Ok(())
+}
+
+/// Parses the value of -n or -c: "N" or "-N" for the last N lines or bytes,
+/// "+N" for everything from the Nth on. N may carry a size suffix such as
+/// `4K` or `1MiB` (see `common::parse_size`).
+pub fn parse_count(val: &str) -> MyResult<Count> {
+    let (sign, number) = match val.split_at(val.len().min(1)) {
+        (sign @ ("+" | "-"), number) => (sign, number),
+        _ => ("", val),
+    };
+    if !sign.is_empty() && number.starts_with('+') {
+        return Err(Error::Usage(val.to_string()));
+    }
+    // The message names the value after the sign, so put it back
+    let count = parse_size(number).map_err(|e| Error::Usage(format!("{}{}", sign, e)))?;
+    match sign {
+        "+" => Ok(Count::From(count)),
+        _ => Ok(Count::Last(count)),
+    }
+}
+
+/// Parses a -s interval: a non-negative number of seconds, which may have
+/// a fractional part.
+pub fn parse_interval(val: &str) -> MyResult<Duration> {
+    val.parse::<f64>()
+        .ok()
+        .filter(|_| val.bytes().all(|b| b.is_ascii_digit() || b == b'.'))
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| Error::Usage(val.to_string()))
+}
+
+pub fn get_args() -> MyResult<Config> {
+    get_args_from(std::env::args_os())
+}
+
+/// Like `get_args`, but parses `args` (whose first item is the program name)
+/// instead of the process arguments.
+pub fn get_args_from<I, T>(args: I) -> MyResult<Config>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let mut matches = Command::new("tailr")
+        .version("0.1.0")
+        .author("Derek Warner <derekw3@illinois.edu>")
+        .about("A rusty tail")
+        .arg(
+            Arg::new("files")
+                .help("Input file(s)")
+                .default_value("-")
+                .num_args(1..),
+        )
+        .arg(
+            Arg::new("number_lines")
+                .short('n')
+                .long("lines")
+                .value_name("LINES")
+                .help("Number of lines, or +N to start at line N")
+                .num_args(1)
+                .allow_hyphen_values(true)
+                .default_value("10"),
+        )
+        .arg(
+            Arg::new("bytes")
+                .short('c')
+                .long("bytes")
+                .value_name("BYTES")
+                .help("Number of bytes, or +N to start at byte N")
+                .num_args(1)
+                .allow_hyphen_values(true)
+                .conflicts_with("number_lines"),
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .visible_alias("silent")
+                .help("Never print headers giving file names")
+                .num_args(0)
+                .overrides_with("verbose"),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("Always print headers giving file names")
+                .num_args(0)
+                .overrides_with("quiet"),
+        )
+        .arg(
+            Arg::new("follow")
+                .short('f')
+                .long("follow")
+                .value_name("HOW")
+                .help("Output data as the files grow; HOW is 'descriptor' or 'name'")
+                .num_args(0..=1)
+                .require_equals(true)
+                .value_parser(["descriptor", "name"])
+                .default_missing_value("descriptor"),
+        )
+        .arg(
+            Arg::new("follow_name")
+                .short('F')
+                .help("Same as --follow=name: reopen files that are rotated or replaced")
+                .num_args(0)
+                .overrides_with("follow"),
+        )
+        .arg(
+            Arg::new("sleep_interval")
+                .short('s')
+                .long("sleep-interval")
+                .value_name("N")
+                .help("With -f, check for changes at least every N seconds")
+                .num_args(1)
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("pid")
+                .long("pid")
+                .value_name("PID")
+                .help("With -f, stop after process PID exits")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("disable_inotify")
+                .long("disable-inotify")
+                .help("With -f, poll instead of waiting on inotify")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .hide(true),
+        )
+        .get_matches_from(args);
+
+    let files: Vec<String> = matches.remove_many("files").unwrap().collect();
+
+    let mut lines = Count::Last(10);
+    if flag(&matches, "number_lines") {
+        let value: String = matches.remove_one("number_lines").unwrap();
+        lines = parse_count(&value)
+            .map_err(|e| Error::Usage(format!("illegal line count -- {}", e)))?;
+    }
+
+    let bytes = match matches.remove_one::<String>("bytes") {
+        Some(value) => Some(
+            parse_count(&value)
+                .map_err(|e| Error::Usage(format!("illegal byte count -- {}", e)))?,
+        ),
+        None => None,
+    };
+
+    let follow = match matches.remove_one::<String>("follow").as_deref() {
+        _ if flag(&matches, "follow_name") => Some(Follow::Name),
+        Some("name") => Some(Follow::Name),
+        Some(_) => Some(Follow::Descriptor),
+        None => None,
+    };
+
+    let interval: String = matches.remove_one("sleep_interval").unwrap();
+    let sleep = parse_interval(&interval)
+        .map_err(|e| Error::Usage(format!("illegal sleep interval -- {}", e)))?;
+
+    let pid = match matches.remove_one::<String>("pid") {
+        Some(value) => match value.parse::<i32>() {
+            Ok(pid) if pid > 0 => Some(pid),
+            _ => return Err(Error::Usage(format!("illegal process id -- {}", value))),
+        },
+        None => None,
+    };
+
+    Ok(Config {
+        files,
+        lines,
+        bytes,
+        quiet: flag(&matches, "quiet"),
+        verbose: flag(&matches, "verbose"),
+        follow,
+        sleep,
+        pid,
+        inotify: !matches.get_flag("disable_inotify"),
+    })
+}
+
+#[test]
+fn test_parse_count() {
+    assert_eq!(parse_count("3").unwrap(), Count::Last(3));
+    assert_eq!(parse_count("-3").unwrap(), Count::Last(3));
+    assert_eq!(parse_count("0").unwrap(), Count::Last(0));
+    assert_eq!(parse_count("+3").unwrap(), Count::From(3));
+    assert_eq!(parse_count("+0").unwrap(), Count::From(0));
+    assert_eq!(parse_count("2K").unwrap(), Count::Last(2048));
+    assert_eq!(parse_count("+1kB").unwrap(), Count::From(1000));
+
+    for bad in [
+        "", "+", "-", "testy", "-testy", "+testy", "++3", "+-3", "4X",
+    ] {
+        let result = parse_count(bad);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), bad.to_string());
+    }
+
+    let result = parse_count("+16E");
+    assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "+16E: Value too large for defined data type".to_string()
+    );
+}
+
+#[test]
+fn test_parse_interval() {
+    assert_eq!(parse_interval("1").unwrap(), Duration::from_secs(1));
+    assert_eq!(parse_interval("0").unwrap(), Duration::ZERO);
+    assert_eq!(parse_interval("0.25").unwrap(), Duration::from_millis(250));
+    assert_eq!(parse_interval(".5").unwrap(), Duration::from_millis(500));
+
+    for bad in ["", "-1", "+1", "1s", "inf", "NaN", "1e3", "1..2"] {
+        let result = parse_interval(bad);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), bad.to_string());
+    }
+}
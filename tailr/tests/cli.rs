@@ -0,0 +1,960 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use rand::{distributions::Alphanumeric, Rng};
+use std::io::Read;
+use std::process::{Child, ChildStderr, ChildStdout, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use std::{error::Error, fs};
+use tempfile::TempDir;
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+const PRG: &str = "tailr";
+const EMPTY: &str = "./tests/inputs/empty.txt";
+const ONE: &str = "./tests/inputs/one.txt";
+const TWO: &str = "./tests/inputs/two.txt";
+const THREE: &str = "./tests/inputs/three.txt";
+const TEN: &str = "./tests/inputs/ten.txt";
+const BINARY: &str = "./tests/inputs/binary.bin";
+const NUMBERS: &str = "./tests/inputs/numbers.txt";
+
+// --------------------------------------------------
+fn random_string() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(7)
+        .map(char::from)
+        .collect()
+}
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename = random_string();
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_bytes() -> TestResult {
+    let bad = random_string();
+    let expected = format!("illegal byte count -- {}", &bad);
+    Command::cargo_bin(PRG)?
+        .args(["-c", &bad, EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(expected));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_lines() -> TestResult {
+    let bad = random_string();
+    let expected = format!("illegal line count -- {}", &bad);
+    Command::cargo_bin(PRG)?
+        .args(["-n", &bad, EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(expected));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_plus_count() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-n", "+4X", EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("illegal line count -- +4X"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bytes_and_lines() -> TestResult {
+    let msg = "the argument '--lines <LINES>' cannot be \
+               used with '--bytes <BYTES>'";
+
+    Command::cargo_bin(PRG)?
+        .args(["-n", "1", "-c", "2"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(msg));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file() -> TestResult {
+    let bad = gen_bad_file();
+    let expected = format!("{}: .* [(]os error 2[)]", bad);
+    Command::cargo_bin(PRG)?
+        .args([EMPTY, &bad, ONE])
+        .assert()
+        .stderr(predicate::str::is_match(expected)?);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> TestResult {
+    let expected = fs::read(expected_file)?;
+
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run_stdin(args: &[&str], input_file: &str, expected_file: &str) -> TestResult {
+    let expected = fs::read(expected_file)?;
+    let input = fs::read(input_file)?;
+
+    Command::cargo_bin(PRG)?
+        .write_stdin(input)
+        .args(args)
+        .assert()
+        .stdout(expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn empty() -> TestResult {
+    run(&[EMPTY], "tests/expected/empty.txt.out")
+}
+
+#[test]
+fn empty_n0() -> TestResult {
+    run(&[EMPTY, "-n", "0"], "tests/expected/empty.txt.n0.out")
+}
+
+#[test]
+fn empty_n2() -> TestResult {
+    run(&[EMPTY, "-n", "2"], "tests/expected/empty.txt.n2.out")
+}
+
+#[test]
+fn empty_n4() -> TestResult {
+    run(&[EMPTY, "-n", "4"], "tests/expected/empty.txt.n4.out")
+}
+
+#[test]
+fn empty_n_plus0() -> TestResult {
+    run(&[EMPTY, "-n", "+0"], "tests/expected/empty.txt.n+0.out")
+}
+
+#[test]
+fn empty_n_plus2() -> TestResult {
+    run(&[EMPTY, "-n", "+2"], "tests/expected/empty.txt.n+2.out")
+}
+
+#[test]
+fn empty_n_minus4() -> TestResult {
+    run(&[EMPTY, "-n", "-4"], "tests/expected/empty.txt.n-4.out")
+}
+
+#[test]
+fn empty_c2() -> TestResult {
+    run(&[EMPTY, "-c", "2"], "tests/expected/empty.txt.c2.out")
+}
+
+#[test]
+fn empty_c4() -> TestResult {
+    run(&[EMPTY, "-c", "4"], "tests/expected/empty.txt.c4.out")
+}
+
+#[test]
+fn empty_c_plus4() -> TestResult {
+    run(&[EMPTY, "-c", "+4"], "tests/expected/empty.txt.c+4.out")
+}
+
+#[test]
+fn empty_c_minus8() -> TestResult {
+    run(&[EMPTY, "-c", "-8"], "tests/expected/empty.txt.c-8.out")
+}
+
+#[test]
+fn empty_n2_stdin() -> TestResult {
+    run_stdin(&["-n", "2"], EMPTY, "tests/expected/empty.txt.n2.out")
+}
+
+#[test]
+fn empty_n_plus2_stdin() -> TestResult {
+    run_stdin(&["-n", "+2"], EMPTY, "tests/expected/empty.txt.n+2.out")
+}
+
+#[test]
+fn empty_c4_stdin() -> TestResult {
+    run_stdin(&["-c", "4"], EMPTY, "tests/expected/empty.txt.c4.out")
+}
+
+#[test]
+fn empty_c_plus4_stdin() -> TestResult {
+    run_stdin(&["-c", "+4"], EMPTY, "tests/expected/empty.txt.c+4.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn one() -> TestResult {
+    run(&[ONE], "tests/expected/one.txt.out")
+}
+
+#[test]
+fn one_n0() -> TestResult {
+    run(&[ONE, "-n", "0"], "tests/expected/one.txt.n0.out")
+}
+
+#[test]
+fn one_n2() -> TestResult {
+    run(&[ONE, "-n", "2"], "tests/expected/one.txt.n2.out")
+}
+
+#[test]
+fn one_n4() -> TestResult {
+    run(&[ONE, "-n", "4"], "tests/expected/one.txt.n4.out")
+}
+
+#[test]
+fn one_n_plus0() -> TestResult {
+    run(&[ONE, "-n", "+0"], "tests/expected/one.txt.n+0.out")
+}
+
+#[test]
+fn one_n_plus2() -> TestResult {
+    run(&[ONE, "-n", "+2"], "tests/expected/one.txt.n+2.out")
+}
+
+#[test]
+fn one_n_minus4() -> TestResult {
+    run(&[ONE, "-n", "-4"], "tests/expected/one.txt.n-4.out")
+}
+
+#[test]
+fn one_c2() -> TestResult {
+    run(&[ONE, "-c", "2"], "tests/expected/one.txt.c2.out")
+}
+
+#[test]
+fn one_c4() -> TestResult {
+    run(&[ONE, "-c", "4"], "tests/expected/one.txt.c4.out")
+}
+
+#[test]
+fn one_c_plus4() -> TestResult {
+    run(&[ONE, "-c", "+4"], "tests/expected/one.txt.c+4.out")
+}
+
+#[test]
+fn one_c_minus8() -> TestResult {
+    run(&[ONE, "-c", "-8"], "tests/expected/one.txt.c-8.out")
+}
+
+#[test]
+fn one_n2_stdin() -> TestResult {
+    run_stdin(&["-n", "2"], ONE, "tests/expected/one.txt.n2.out")
+}
+
+#[test]
+fn one_n_plus2_stdin() -> TestResult {
+    run_stdin(&["-n", "+2"], ONE, "tests/expected/one.txt.n+2.out")
+}
+
+#[test]
+fn one_c4_stdin() -> TestResult {
+    run_stdin(&["-c", "4"], ONE, "tests/expected/one.txt.c4.out")
+}
+
+#[test]
+fn one_c_plus4_stdin() -> TestResult {
+    run_stdin(&["-c", "+4"], ONE, "tests/expected/one.txt.c+4.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn two() -> TestResult {
+    run(&[TWO], "tests/expected/two.txt.out")
+}
+
+#[test]
+fn two_n0() -> TestResult {
+    run(&[TWO, "-n", "0"], "tests/expected/two.txt.n0.out")
+}
+
+#[test]
+fn two_n2() -> TestResult {
+    run(&[TWO, "-n", "2"], "tests/expected/two.txt.n2.out")
+}
+
+#[test]
+fn two_n4() -> TestResult {
+    run(&[TWO, "-n", "4"], "tests/expected/two.txt.n4.out")
+}
+
+#[test]
+fn two_n_plus0() -> TestResult {
+    run(&[TWO, "-n", "+0"], "tests/expected/two.txt.n+0.out")
+}
+
+#[test]
+fn two_n_plus2() -> TestResult {
+    run(&[TWO, "-n", "+2"], "tests/expected/two.txt.n+2.out")
+}
+
+#[test]
+fn two_n_minus4() -> TestResult {
+    run(&[TWO, "-n", "-4"], "tests/expected/two.txt.n-4.out")
+}
+
+#[test]
+fn two_c2() -> TestResult {
+    run(&[TWO, "-c", "2"], "tests/expected/two.txt.c2.out")
+}
+
+#[test]
+fn two_c4() -> TestResult {
+    run(&[TWO, "-c", "4"], "tests/expected/two.txt.c4.out")
+}
+
+#[test]
+fn two_c_plus4() -> TestResult {
+    run(&[TWO, "-c", "+4"], "tests/expected/two.txt.c+4.out")
+}
+
+#[test]
+fn two_c_minus8() -> TestResult {
+    run(&[TWO, "-c", "-8"], "tests/expected/two.txt.c-8.out")
+}
+
+#[test]
+fn two_n2_stdin() -> TestResult {
+    run_stdin(&["-n", "2"], TWO, "tests/expected/two.txt.n2.out")
+}
+
+#[test]
+fn two_n_plus2_stdin() -> TestResult {
+    run_stdin(&["-n", "+2"], TWO, "tests/expected/two.txt.n+2.out")
+}
+
+#[test]
+fn two_c4_stdin() -> TestResult {
+    run_stdin(&["-c", "4"], TWO, "tests/expected/two.txt.c4.out")
+}
+
+#[test]
+fn two_c_plus4_stdin() -> TestResult {
+    run_stdin(&["-c", "+4"], TWO, "tests/expected/two.txt.c+4.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn three() -> TestResult {
+    run(&[THREE], "tests/expected/three.txt.out")
+}
+
+#[test]
+fn three_n0() -> TestResult {
+    run(&[THREE, "-n", "0"], "tests/expected/three.txt.n0.out")
+}
+
+#[test]
+fn three_n2() -> TestResult {
+    run(&[THREE, "-n", "2"], "tests/expected/three.txt.n2.out")
+}
+
+#[test]
+fn three_n4() -> TestResult {
+    run(&[THREE, "-n", "4"], "tests/expected/three.txt.n4.out")
+}
+
+#[test]
+fn three_n_plus0() -> TestResult {
+    run(&[THREE, "-n", "+0"], "tests/expected/three.txt.n+0.out")
+}
+
+#[test]
+fn three_n_plus2() -> TestResult {
+    run(&[THREE, "-n", "+2"], "tests/expected/three.txt.n+2.out")
+}
+
+#[test]
+fn three_n_minus4() -> TestResult {
+    run(&[THREE, "-n", "-4"], "tests/expected/three.txt.n-4.out")
+}
+
+#[test]
+fn three_c2() -> TestResult {
+    run(&[THREE, "-c", "2"], "tests/expected/three.txt.c2.out")
+}
+
+#[test]
+fn three_c4() -> TestResult {
+    run(&[THREE, "-c", "4"], "tests/expected/three.txt.c4.out")
+}
+
+#[test]
+fn three_c_plus4() -> TestResult {
+    run(&[THREE, "-c", "+4"], "tests/expected/three.txt.c+4.out")
+}
+
+#[test]
+fn three_c_minus8() -> TestResult {
+    run(&[THREE, "-c", "-8"], "tests/expected/three.txt.c-8.out")
+}
+
+#[test]
+fn three_n2_stdin() -> TestResult {
+    run_stdin(&["-n", "2"], THREE, "tests/expected/three.txt.n2.out")
+}
+
+#[test]
+fn three_n_plus2_stdin() -> TestResult {
+    run_stdin(&["-n", "+2"], THREE, "tests/expected/three.txt.n+2.out")
+}
+
+#[test]
+fn three_c4_stdin() -> TestResult {
+    run_stdin(&["-c", "4"], THREE, "tests/expected/three.txt.c4.out")
+}
+
+#[test]
+fn three_c_plus4_stdin() -> TestResult {
+    run_stdin(&["-c", "+4"], THREE, "tests/expected/three.txt.c+4.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn ten() -> TestResult {
+    run(&[TEN], "tests/expected/ten.txt.out")
+}
+
+#[test]
+fn ten_n0() -> TestResult {
+    run(&[TEN, "-n", "0"], "tests/expected/ten.txt.n0.out")
+}
+
+#[test]
+fn ten_n2() -> TestResult {
+    run(&[TEN, "-n", "2"], "tests/expected/ten.txt.n2.out")
+}
+
+#[test]
+fn ten_n4() -> TestResult {
+    run(&[TEN, "-n", "4"], "tests/expected/ten.txt.n4.out")
+}
+
+#[test]
+fn ten_n_plus0() -> TestResult {
+    run(&[TEN, "-n", "+0"], "tests/expected/ten.txt.n+0.out")
+}
+
+#[test]
+fn ten_n_plus2() -> TestResult {
+    run(&[TEN, "-n", "+2"], "tests/expected/ten.txt.n+2.out")
+}
+
+#[test]
+fn ten_n_minus4() -> TestResult {
+    run(&[TEN, "-n", "-4"], "tests/expected/ten.txt.n-4.out")
+}
+
+#[test]
+fn ten_c2() -> TestResult {
+    run(&[TEN, "-c", "2"], "tests/expected/ten.txt.c2.out")
+}
+
+#[test]
+fn ten_c4() -> TestResult {
+    run(&[TEN, "-c", "4"], "tests/expected/ten.txt.c4.out")
+}
+
+#[test]
+fn ten_c_plus4() -> TestResult {
+    run(&[TEN, "-c", "+4"], "tests/expected/ten.txt.c+4.out")
+}
+
+#[test]
+fn ten_c_minus8() -> TestResult {
+    run(&[TEN, "-c", "-8"], "tests/expected/ten.txt.c-8.out")
+}
+
+#[test]
+fn ten_n2_stdin() -> TestResult {
+    run_stdin(&["-n", "2"], TEN, "tests/expected/ten.txt.n2.out")
+}
+
+#[test]
+fn ten_n_plus2_stdin() -> TestResult {
+    run_stdin(&["-n", "+2"], TEN, "tests/expected/ten.txt.n+2.out")
+}
+
+#[test]
+fn ten_c4_stdin() -> TestResult {
+    run_stdin(&["-c", "4"], TEN, "tests/expected/ten.txt.c4.out")
+}
+
+#[test]
+fn ten_c_plus4_stdin() -> TestResult {
+    run_stdin(&["-c", "+4"], TEN, "tests/expected/ten.txt.c+4.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_c8() -> TestResult {
+    run(&[BINARY, "-c", "8"], "tests/expected/binary.bin.c8.out")
+}
+
+#[test]
+fn binary_n2() -> TestResult {
+    run(&[BINARY, "-n", "2"], "tests/expected/binary.bin.n2.out")
+}
+
+#[test]
+fn binary_c_plus30() -> TestResult {
+    run(&[BINARY, "-c", "+30"], "tests/expected/binary.bin.c+30.out")
+}
+
+#[test]
+fn binary_c8_stdin() -> TestResult {
+    run_stdin(&["-c", "8"], BINARY, "tests/expected/binary.bin.c8.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn numbers_c1k() -> TestResult {
+    run(&[NUMBERS, "-c", "1K"], "tests/expected/numbers.txt.c1K.out")
+}
+
+#[test]
+fn numbers_c_plus2kb() -> TestResult {
+    run(
+        &[NUMBERS, "-c", "+2kB"],
+        "tests/expected/numbers.txt.c+2kB.out",
+    )
+}
+
+#[test]
+fn numbers_n1k() -> TestResult {
+    run(&[NUMBERS, "-n", "1k"], "tests/expected/numbers.txt.n1k.out")
+}
+
+#[test]
+fn numbers_n_plus1k() -> TestResult {
+    run(
+        &[NUMBERS, "-n", "+1k"],
+        "tests/expected/numbers.txt.n+1k.out",
+    )
+}
+
+#[test]
+fn numbers_n100() -> TestResult {
+    run(
+        &[NUMBERS, "-n", "100"],
+        "tests/expected/numbers.txt.n100.out",
+    )
+}
+
+#[test]
+fn numbers_n100_stdin() -> TestResult {
+    run_stdin(
+        &["-n", "100"],
+        NUMBERS,
+        "tests/expected/numbers.txt.n100.out",
+    )
+}
+
+#[test]
+fn numbers_c1k_stdin() -> TestResult {
+    run_stdin(&["-c", "1K"], NUMBERS, "tests/expected/numbers.txt.c1K.out")
+}
+
+#[test]
+fn numbers_n_plus1k_stdin() -> TestResult {
+    run_stdin(
+        &["-n", "+1k"],
+        NUMBERS,
+        "tests/expected/numbers.txt.n+1k.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_files() -> TestResult {
+    run(&[EMPTY, ONE, TWO, THREE, TEN], "tests/expected/all.out")
+}
+
+#[test]
+fn multiple_files_n2() -> TestResult {
+    run(
+        &[EMPTY, ONE, TWO, THREE, TEN, "-n", "2"],
+        "tests/expected/all.n2.out",
+    )
+}
+
+#[test]
+fn multiple_files_n_plus3() -> TestResult {
+    run(
+        &[EMPTY, ONE, TWO, THREE, TEN, "-n", "+3"],
+        "tests/expected/all.n+3.out",
+    )
+}
+
+#[test]
+fn multiple_files_c4() -> TestResult {
+    run(
+        &[EMPTY, ONE, TWO, THREE, TEN, "-c", "4"],
+        "tests/expected/all.c4.out",
+    )
+}
+
+#[test]
+fn multiple_files_quiet() -> TestResult {
+    run(
+        &["-q", EMPTY, ONE, TWO, THREE, TEN],
+        "tests/expected/all.q.out",
+    )
+}
+
+#[test]
+fn multiple_files_verbose_then_quiet() -> TestResult {
+    run(
+        &["-v", "-q", EMPTY, ONE, TWO, THREE, TEN],
+        "tests/expected/all.vq.out",
+    )
+}
+
+#[test]
+fn ten_verbose() -> TestResult {
+    run(&["-v", TEN], "tests/expected/ten.txt.v.out")
+}
+
+#[test]
+fn three_verbose_stdin() -> TestResult {
+    run_stdin(
+        &["-v", "-n", "2"],
+        THREE,
+        "tests/expected/three.txt.v.stdin.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_sleep_interval() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-f", "-s", "1s", EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("illegal sleep interval -- 1s"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_pid() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-f", "--pid", "0", EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("illegal process id -- 0"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_files_nothing_to_print() -> TestResult {
+    for count in [["-n", "0"], ["-c", "0"]] {
+        Command::cargo_bin(PRG)?
+            .args(count)
+            .args([ONE, TWO])
+            .assert()
+            .success()
+            .stdout("");
+    }
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_no_files_to_follow() -> TestResult {
+    let bad = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args(["-f", &bad])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no files remaining"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_pipe_ends() -> TestResult {
+    // A pipe cannot grow once read to the end, so there is nothing to follow
+    Command::cargo_bin(PRG)?
+        .args(["-f", "-n", "4"])
+        .write_stdin(fs::read(TEN)?)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(fs::read("tests/expected/ten.txt.n4.out")?);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+/// A `tailr -f` run in the background, killed when dropped.
+struct Following {
+    child: Child,
+    stdout: Arc<Mutex<Vec<u8>>>,
+    stderr: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Following {
+    fn start(args: &[&str]) -> Result<Following, Box<dyn Error>> {
+        let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin(PRG))
+            .args(["-s", "0.1"])
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = collect(child.stdout.take().unwrap());
+        let stderr = collect(child.stderr.take().unwrap());
+        Ok(Following {
+            child,
+            stdout,
+            stderr,
+        })
+    }
+
+    /// Waits for stdout to read `expected`, returning what it last read.
+    fn stdout(&self, expected: &str) -> String {
+        wait_for(&self.stdout, |output| output == expected)
+    }
+
+    /// Waits for stderr to mention `expected`, returning what it last read.
+    fn stderr(&self, expected: &str) -> String {
+        wait_for(&self.stderr, |output| output.contains(expected))
+    }
+}
+
+impl Drop for Following {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+trait Pipe: Read + Send + 'static {}
+impl Pipe for ChildStdout {}
+impl Pipe for ChildStderr {}
+
+fn collect(mut pipe: impl Pipe) -> Arc<Mutex<Vec<u8>>> {
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let collected = Arc::clone(&output);
+    thread::spawn(move || {
+        let mut buffer = [0; 1024];
+        while let Ok(bytes @ 1..) = pipe.read(&mut buffer) {
+            collected
+                .lock()
+                .unwrap()
+                .extend_from_slice(&buffer[..bytes]);
+        }
+    });
+    output
+}
+
+fn wait_for(output: &Mutex<Vec<u8>>, done: impl Fn(&str) -> bool) -> String {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let text = String::from_utf8_lossy(&output.lock().unwrap()).into_owned();
+        if done(&text) || Instant::now() > deadline {
+            return text;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+fn append(path: &std::path::Path, text: &str) -> TestResult {
+    use std::io::Write;
+    fs::OpenOptions::new()
+        .append(true)
+        .open(path)?
+        .write_all(text.as_bytes())?;
+    Ok(())
+}
+
+// --------------------------------------------------
+fn follows_appended_data(extra: &[&str]) -> TestResult {
+    let dir = TempDir::new()?;
+    let log = dir.path().join("log");
+    fs::write(&log, "one\ntwo\n")?;
+
+    let mut args = vec!["-f", "-n", "1", log.to_str().unwrap()];
+    args.extend(extra);
+    let tail = Following::start(&args)?;
+    assert_eq!(tail.stdout("two\n"), "two\n");
+    append(&log, "three\n")?;
+    assert_eq!(tail.stdout("two\nthree\n"), "two\nthree\n");
+    append(&log, "four")?;
+    assert_eq!(tail.stdout("two\nthree\nfour"), "two\nthree\nfour");
+
+    Ok(())
+}
+
+#[test]
+fn follow_descriptor() -> TestResult {
+    follows_appended_data(&[])
+}
+
+#[test]
+fn follow_descriptor_polling() -> TestResult {
+    follows_appended_data(&["--disable-inotify"])
+}
+
+#[test]
+fn follow_name_polling() -> TestResult {
+    follows_appended_data(&["-F", "--disable-inotify"])
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_headers_on_switch() -> TestResult {
+    let dir = TempDir::new()?;
+    let (a, b) = (dir.path().join("a"), dir.path().join("b"));
+    fs::write(&a, "a1\n")?;
+    fs::write(&b, "b1\n")?;
+    let (a_name, b_name) = (a.to_str().unwrap(), b.to_str().unwrap());
+
+    let tail = Following::start(&["-f", a_name, b_name])?;
+    let mut expected = format!("==> {} <==\na1\n\n==> {} <==\nb1\n", a_name, b_name);
+    assert_eq!(tail.stdout(&expected), expected);
+
+    // The file printed last needs no second header
+    append(&b, "b2\n")?;
+    expected += "b2\n";
+    assert_eq!(tail.stdout(&expected), expected);
+
+    append(&a, "a2\n")?;
+    expected += &format!("\n==> {} <==\na2\n", a_name);
+    assert_eq!(tail.stdout(&expected), expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_truncated() -> TestResult {
+    let dir = TempDir::new()?;
+    let log = dir.path().join("log");
+    fs::write(&log, "a long first line\n")?;
+
+    let tail = Following::start(&["-f", log.to_str().unwrap()])?;
+    assert_eq!(tail.stdout("a long first line\n"), "a long first line\n");
+    fs::write(&log, "short\n")?;
+    let expected = "a long first line\nshort\n";
+    assert_eq!(tail.stdout(expected), expected);
+    assert!(tail.stderr("file truncated").contains("file truncated"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_name_rotated() -> TestResult {
+    let dir = TempDir::new()?;
+    let log = dir.path().join("log");
+    fs::write(&log, "old\n")?;
+
+    let tail = Following::start(&["-F", log.to_str().unwrap()])?;
+    assert_eq!(tail.stdout("old\n"), "old\n");
+
+    // Replace the log in one step, as logrotate's create mode does
+    let new = dir.path().join("log.new");
+    fs::write(&new, "new\n")?;
+    fs::rename(&new, &log)?;
+    assert_eq!(tail.stdout("old\nnew\n"), "old\nnew\n");
+    assert!(tail
+        .stderr("has been replaced")
+        .contains("has been replaced"));
+
+    append(&log, "newer\n")?;
+    assert_eq!(tail.stdout("old\nnew\nnewer\n"), "old\nnew\nnewer\n");
+
+    Ok(())
+}
+
+#[test]
+fn follow_descriptor_after_rename() -> TestResult {
+    let dir = TempDir::new()?;
+    let log = dir.path().join("log");
+    fs::write(&log, "old\n")?;
+
+    let tail = Following::start(&["-f", log.to_str().unwrap()])?;
+    assert_eq!(tail.stdout("old\n"), "old\n");
+
+    // -f keeps reading the renamed file and ignores the new one
+    let moved = dir.path().join("log.1");
+    fs::rename(&log, &moved)?;
+    fs::write(&log, "new\n")?;
+    append(&moved, "moved\n")?;
+    assert_eq!(tail.stdout("old\nmoved\n"), "old\nmoved\n");
+
+    Ok(())
+}
+
+#[test]
+fn follow_name_appears() -> TestResult {
+    let dir = TempDir::new()?;
+    let log = dir.path().join("log");
+
+    let tail = Following::start(&["-F", log.to_str().unwrap()])?;
+    assert!(tail.stderr("os error 2").contains("os error 2"));
+    fs::write(&log, "hello\n")?;
+    assert_eq!(tail.stdout("hello\n"), "hello\n");
+    assert!(tail.stderr("has appeared").contains("has appeared"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_until_pid_exits() -> TestResult {
+    let dir = TempDir::new()?;
+    let log = dir.path().join("log");
+    fs::write(&log, "line\n")?;
+
+    let mut writer = std::process::Command::new("sleep").arg("0.5").spawn()?;
+    let pid = writer.id().to_string();
+    let mut tail = Following::start(&["-f", "--pid", &pid, log.to_str().unwrap()])?;
+    assert_eq!(tail.stdout("line\n"), "line\n");
+
+    // Reap the writer so it does not linger as a zombie
+    writer.wait()?;
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while tail.child.try_wait()?.is_none() {
+        assert!(Instant::now() < deadline, "tailr still following");
+        thread::sleep(Duration::from_millis(20));
+    }
+    assert!(tail.child.wait()?.success());
+
+    Ok(())
+}
@@ -0,0 +1,317 @@
+use clap::{Arg, Command};
+use common::{flag, open, Error, MyResult};
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+
+#[derive(Debug)]
+pub struct Config {
+    in_file: String,
+    out_file: Option<String>,
+    count: bool,
+    repeated: bool,
+    all_repeated: bool,
+    unique: bool,
+    ignore_case: bool,
+    skip_fields: usize,
+    skip_chars: usize,
+    check_chars: Option<usize>,
+    zero_terminated: bool,
+}
+
+pub fn run(config: Config) -> MyResult<()> {
+    let mut file = open(&config.in_file).map_err(|e| e.in_file(&config.in_file))?;
+    let mut out: Box<dyn Write> = match config.out_file.as_deref() {
+        None | Some("-") => Box::new(BufWriter::new(io::stdout())),
+        Some(name) => Box::new(BufWriter::new(
+            File::create(name).map_err(|e| Error::from(e).in_file(name))?,
+        )),
+    };
+
+    let delimiter = if config.zero_terminated { b'\0' } else { b'\n' };
+    let mut group = Group {
+        config: &config,
+        first: Vec::new(),
+        count: 0,
+        delimiter,
+    };
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if file.read_until(delimiter, &mut line)? == 0 {
+            break;
+        }
+        if line.last() == Some(&delimiter) {
+            line.pop();
+        }
+        group.add(&line, &mut out)?;
+    }
+    group.finish(&mut out)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// A run of adjacent lines that compare equal.
+struct Group<'a> {
+    config: &'a Config,
+    /// The line that started the group, without its delimiter.
+    first: Vec<u8>,
+    count: usize,
+    delimiter: u8,
+}
+
+impl Group<'_> {
+    /// Adds `line` to the group, or prints the group and starts a new one
+    /// when it does not match. With -D lines are printed as they arrive.
+    fn add(&mut self, line: &[u8], out: &mut impl Write) -> io::Result<()> {
+        if self.count > 0 && self.matches(line) {
+            self.count += 1;
+            if self.config.all_repeated {
+                // -u leaves out the first line of each repeated group
+                if self.count == 2 && !self.config.unique {
+                    self.write_line(&self.first, out)?;
+                }
+                self.write_line(line, out)?;
+            }
+            return Ok(());
+        }
+
+        self.finish(out)?;
+        self.first.clear();
+        self.first.extend_from_slice(line);
+        self.count = 1;
+        Ok(())
+    }
+
+    /// Prints the group's line, if the options select it.
+    fn finish(&mut self, out: &mut impl Write) -> io::Result<()> {
+        if self.count == 0 || self.config.all_repeated {
+            return Ok(());
+        }
+        let show = match (self.config.repeated, self.config.unique) {
+            (false, false) => true,
+            (true, false) => self.count > 1,
+            (false, true) => self.count == 1,
+            (true, true) => false,
+        };
+        if show {
+            if self.config.count {
+                write!(out, "{:>7} ", self.count)?;
+            }
+            self.write_line(&self.first, out)?;
+        }
+        Ok(())
+    }
+
+    fn write_line(&self, line: &[u8], out: &mut impl Write) -> io::Result<()> {
+        out.write_all(line)?;
+        out.write_all(&[self.delimiter])
+    }
+
+    fn matches(&self, line: &[u8]) -> bool {
+        let (a, b) = (key(&self.first, self.config), key(line, self.config));
+        match self.config.ignore_case {
+            true => a.eq_ignore_ascii_case(b),
+            false => a == b,
+        }
+    }
+}
+
+/// The part of `line` that is compared: what follows the fields skipped
+/// with -f and the bytes skipped with -s, cut to -w bytes. A field is a
+/// run of blanks followed by a run of anything else.
+fn key<'a>(line: &'a [u8], config: &Config) -> &'a [u8] {
+    let blank = |b: &u8| *b == b' ' || *b == b'\t';
+    let mut rest = line;
+    for _ in 0..config.skip_fields {
+        if rest.is_empty() {
+            break;
+        }
+        let start = rest.iter().position(|b| !blank(b)).unwrap_or(rest.len());
+        rest = &rest[start..];
+        let end = rest.iter().position(blank).unwrap_or(rest.len());
+        rest = &rest[end..];
+    }
+    rest = &rest[config.skip_chars.min(rest.len())..];
+    match config.check_chars {
+        Some(n) => &rest[..n.min(rest.len())],
+        None => rest,
+    }
+}
+
+/// Parses a count for -f, -s or -w, which may be zero.
+fn parse_count(val: &str) -> Result<usize, String> {
+    if val.is_empty() || !val.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(val.to_string());
+    }
+    // Counts past the end of any line all mean the same
+    Ok(val.parse().unwrap_or(usize::MAX))
+}
+
+pub fn get_args() -> MyResult<Config> {
+    get_args_from(std::env::args_os())
+}
+
+/// Like `get_args`, but parses `args` (whose first item is the program name)
+/// instead of the process arguments.
+pub fn get_args_from<I, T>(args: I) -> MyResult<Config>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let mut matches = Command::new("uniqr")
+        .version("0.1.0")
+        .author("Derek Warner <derekw3@illinois.edu>")
+        .about("A rusty uniq")
+        .arg(
+            Arg::new("in_file")
+                .value_name("IN_FILE")
+                .help("Input file")
+                .default_value("-"),
+        )
+        .arg(
+            Arg::new("out_file")
+                .value_name("OUT_FILE")
+                .help("Output file"),
+        )
+        .arg(
+            Arg::new("count")
+                .short('c')
+                .long("count")
+                .help("Prefix lines by the number of occurrences")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("repeated")
+                .short('d')
+                .long("repeated")
+                .help("Only print one copy of each repeated line")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("all_repeated")
+                .short('D')
+                .long("all-repeated")
+                .help("Print every copy of each repeated line")
+                .num_args(0)
+                .conflicts_with("count"),
+        )
+        .arg(
+            Arg::new("unique")
+                .short('u')
+                .long("unique")
+                .help("Only print lines that are not repeated")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("ignore_case")
+                .short('i')
+                .long("ignore-case")
+                .help("Ignore differences in case when comparing")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("skip_fields")
+                .short('f')
+                .long("skip-fields")
+                .value_name("N")
+                .help("Avoid comparing the first N fields")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("skip_chars")
+                .short('s')
+                .long("skip-chars")
+                .value_name("N")
+                .help("Avoid comparing the first N characters")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("check_chars")
+                .short('w')
+                .long("check-chars")
+                .value_name("N")
+                .help("Compare no more than N characters")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("zero_terminated")
+                .short('z')
+                .long("zero-terminated")
+                .help("Line delimiter is NUL, not newline")
+                .num_args(0),
+        )
+        .get_matches_from(args);
+
+    let mut count = |id: &str, what: &str| -> MyResult<Option<usize>> {
+        matches
+            .remove_one::<String>(id)
+            .map(|val| parse_count(&val))
+            .transpose()
+            .map_err(|e| Error::Usage(format!("illegal {} count -- {}", what, e)))
+    };
+    let skip_fields = count("skip_fields", "field")?.unwrap_or(0);
+    let skip_chars = count("skip_chars", "skip")?.unwrap_or(0);
+    let check_chars = count("check_chars", "check")?;
+
+    Ok(Config {
+        in_file: matches.remove_one("in_file").unwrap(),
+        out_file: matches.remove_one("out_file"),
+        count: flag(&matches, "count"),
+        repeated: flag(&matches, "repeated"),
+        all_repeated: flag(&matches, "all_repeated"),
+        unique: flag(&matches, "unique"),
+        ignore_case: flag(&matches, "ignore_case"),
+        skip_fields,
+        skip_chars,
+        check_chars,
+        zero_terminated: flag(&matches, "zero_terminated"),
+    })
+}
+
+#[cfg(test)]
+fn test_config() -> Config {
+    Config {
+        in_file: "-".to_string(),
+        out_file: None,
+        count: false,
+        repeated: false,
+        all_repeated: false,
+        unique: false,
+        ignore_case: false,
+        skip_fields: 0,
+        skip_chars: 0,
+        check_chars: None,
+        zero_terminated: false,
+    }
+}
+
+#[test]
+fn test_key() {
+    let mut config = test_config();
+    assert_eq!(key(b"1 alpha x", &config), b"1 alpha x");
+
+    config.skip_fields = 1;
+    assert_eq!(key(b"1 alpha x", &config), b" alpha x");
+    assert_eq!(key(b"  12\t alpha", &config), b"\t alpha");
+    assert_eq!(key(b"one", &config), b"");
+
+    config.skip_chars = 2;
+    assert_eq!(key(b"1 alpha x", &config), b"lpha x");
+    config.check_chars = Some(3);
+    assert_eq!(key(b"1 alpha x", &config), b"lph");
+    assert_eq!(key(b"1 a", &config), b"");
+
+    config.skip_fields = usize::MAX;
+    assert_eq!(key(b"a b c", &config), b"");
+}
+
+#[test]
+fn test_parse_count() {
+    assert_eq!(parse_count("0"), Ok(0));
+    assert_eq!(parse_count("12"), Ok(12));
+    assert_eq!(parse_count("99999999999999999999999"), Ok(usize::MAX));
+    for bad in ["", "-1", "+1", "1x", "x"] {
+        assert_eq!(parse_count(bad), Err(bad.to_string()));
+    }
+}
@@ -0,0 +1,6 @@
+fn main() {
+    if let Err(e) = uniqr::get_args().and_then(uniqr::run) {
+        eprintln!("{}", e);
+        std::process::exit(e.exit_code());
+    }
+}
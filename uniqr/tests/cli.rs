@@ -0,0 +1,354 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use rand::{distributions::Alphanumeric, Rng};
+use std::{error::Error, fs};
+use tempfile::TempDir;
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+const PRG: &str = "uniqr";
+const EMPTY: &str = "tests/inputs/empty.txt";
+const REPEATS: &str = "tests/inputs/repeats.txt";
+const CASE: &str = "tests/inputs/case.txt";
+const FIELDS: &str = "tests/inputs/fields.txt";
+const ZERO: &str = "tests/inputs/zero.txt";
+
+// --------------------------------------------------
+fn random_string() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(7)
+        .map(char::from)
+        .collect()
+}
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename = random_string();
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_file() -> TestResult {
+    let bad = gen_bad_file();
+    let expected = format!("{}: .* [(]os error 2[)]", bad);
+    Command::cargo_bin(PRG)?
+        .arg(&bad)
+        .assert()
+        .failure()
+        .stderr(predicate::str::is_match(expected)?);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_out_file() -> TestResult {
+    let out = format!("{}/{}", gen_bad_file(), "out.txt");
+    let expected = format!("{}: .* [(]os error 2[)]", out);
+    Command::cargo_bin(PRG)?
+        .args([REPEATS, &out])
+        .assert()
+        .failure()
+        .stderr(predicate::str::is_match(expected)?);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_counts() -> TestResult {
+    for (flag, what) in [("-f", "field"), ("-s", "skip"), ("-w", "check")] {
+        let bad = random_string();
+        let expected = format!("illegal {} count -- {}", what, bad);
+        Command::cargo_bin(PRG)?
+            .args([flag, &bad, EMPTY])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(expected));
+    }
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_all_repeated_count() -> TestResult {
+    let msg = "the argument '--all-repeated' cannot be used with '--count'";
+    Command::cargo_bin(PRG)?
+        .args(["-D", "-c", REPEATS])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(msg));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> TestResult {
+    let expected = fs::read(expected_file)?;
+
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run_stdin(args: &[&str], input_file: &str, expected_file: &str) -> TestResult {
+    let expected = fs::read(expected_file)?;
+    let input = fs::read(input_file)?;
+
+    Command::cargo_bin(PRG)?
+        .write_stdin(input)
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run_outfile(args: &[&str], expected_file: &str) -> TestResult {
+    let expected = fs::read(expected_file)?;
+    let dir = TempDir::new()?;
+    let out = dir.path().join("out.txt");
+
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout("");
+    assert_eq!(fs::read(&out)?, expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn empty() -> TestResult {
+    run(&[EMPTY], "tests/expected/empty.txt.out")
+}
+
+#[test]
+fn empty_c() -> TestResult {
+    run(&["-c", EMPTY], "tests/expected/empty.txt.c.out")
+}
+
+#[test]
+fn empty_c_stdin() -> TestResult {
+    run_stdin(&["-c"], EMPTY, "tests/expected/empty.txt.c.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn repeats() -> TestResult {
+    run(&[REPEATS], "tests/expected/repeats.txt.out")
+}
+
+#[test]
+fn repeats_stdin() -> TestResult {
+    run_stdin(&[], REPEATS, "tests/expected/repeats.txt.out")
+}
+
+#[test]
+fn repeats_outfile() -> TestResult {
+    run_outfile(&[REPEATS], "tests/expected/repeats.txt.out")
+}
+
+#[test]
+fn repeats_c() -> TestResult {
+    run(&["-c", REPEATS], "tests/expected/repeats.txt.c.out")
+}
+
+#[test]
+fn repeats_c_stdin() -> TestResult {
+    run_stdin(&["-c"], REPEATS, "tests/expected/repeats.txt.c.out")
+}
+
+#[test]
+fn repeats_c_outfile() -> TestResult {
+    run_outfile(&["-c", REPEATS], "tests/expected/repeats.txt.c.out")
+}
+
+#[test]
+fn repeats_d() -> TestResult {
+    run(&["-d", REPEATS], "tests/expected/repeats.txt.d.out")
+}
+
+#[test]
+fn repeats_all_repeated() -> TestResult {
+    run(&["-D", REPEATS], "tests/expected/repeats.txt.D.out")
+}
+
+#[test]
+fn repeats_u() -> TestResult {
+    run(&["-u", REPEATS], "tests/expected/repeats.txt.u.out")
+}
+
+#[test]
+fn repeats_d_u() -> TestResult {
+    run(&["-d", "-u", REPEATS], "tests/expected/repeats.txt.du.out")
+}
+
+#[test]
+fn repeats_all_repeated_u() -> TestResult {
+    run(&["-D", "-u", REPEATS], "tests/expected/repeats.txt.Du.out")
+}
+
+#[test]
+fn repeats_all_repeated_d() -> TestResult {
+    run(&["-D", "-d", REPEATS], "tests/expected/repeats.txt.Dd.out")
+}
+
+#[test]
+fn repeats_c_d() -> TestResult {
+    run(&["-c", "-d", REPEATS], "tests/expected/repeats.txt.cd.out")
+}
+
+#[test]
+fn repeats_c_u() -> TestResult {
+    run(&["-c", "-u", REPEATS], "tests/expected/repeats.txt.cu.out")
+}
+
+#[test]
+fn repeats_w0() -> TestResult {
+    run(&["-w", "0", REPEATS], "tests/expected/repeats.txt.w0.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn case() -> TestResult {
+    run(&[CASE], "tests/expected/case.txt.out")
+}
+
+#[test]
+fn case_i() -> TestResult {
+    run(&["-i", CASE], "tests/expected/case.txt.i.out")
+}
+
+#[test]
+fn case_i_stdin() -> TestResult {
+    run_stdin(&["-i"], CASE, "tests/expected/case.txt.i.out")
+}
+
+#[test]
+fn case_i_c() -> TestResult {
+    run(&["-i", "-c", CASE], "tests/expected/case.txt.ic.out")
+}
+
+#[test]
+fn case_i_all_repeated() -> TestResult {
+    run(&["-i", "-D", CASE], "tests/expected/case.txt.iD.out")
+}
+
+#[test]
+fn case_i_u() -> TestResult {
+    run(&["-i", "-u", CASE], "tests/expected/case.txt.iu.out")
+}
+
+#[test]
+fn case_w1() -> TestResult {
+    run(&["-w", "1", CASE], "tests/expected/case.txt.w1.out")
+}
+
+#[test]
+fn case_i_w1() -> TestResult {
+    run(&["-i", "-w", "1", CASE], "tests/expected/case.txt.iw1.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn fields() -> TestResult {
+    run(&[FIELDS], "tests/expected/fields.txt.out")
+}
+
+#[test]
+fn fields_f1() -> TestResult {
+    run(&["-f", "1", FIELDS], "tests/expected/fields.txt.f1.out")
+}
+
+#[test]
+fn fields_f1_stdin() -> TestResult {
+    run_stdin(&["-f", "1"], FIELDS, "tests/expected/fields.txt.f1.out")
+}
+
+#[test]
+fn fields_f1_c() -> TestResult {
+    run(
+        &["-f", "1", "-c", FIELDS],
+        "tests/expected/fields.txt.f1c.out",
+    )
+}
+
+#[test]
+fn fields_f2() -> TestResult {
+    run(&["-f", "2", FIELDS], "tests/expected/fields.txt.f2.out")
+}
+
+#[test]
+fn fields_s2() -> TestResult {
+    run(&["-s", "2", FIELDS], "tests/expected/fields.txt.s2.out")
+}
+
+#[test]
+fn fields_s2_w3() -> TestResult {
+    run(
+        &["-s", "2", "-w", "3", FIELDS],
+        "tests/expected/fields.txt.s2w3.out",
+    )
+}
+
+#[test]
+fn fields_f1_s1() -> TestResult {
+    run(
+        &["-f", "1", "-s", "1", FIELDS],
+        "tests/expected/fields.txt.f1s1.out",
+    )
+}
+
+#[test]
+fn fields_w1() -> TestResult {
+    run(&["-w", "1", FIELDS], "tests/expected/fields.txt.w1.out")
+}
+
+#[test]
+fn fields_f9() -> TestResult {
+    run(&["-f", "9", FIELDS], "tests/expected/fields.txt.f9.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn zero_z() -> TestResult {
+    run(&["-z", ZERO], "tests/expected/zero.txt.z.out")
+}
+
+#[test]
+fn zero_z_stdin() -> TestResult {
+    run_stdin(&["-z"], ZERO, "tests/expected/zero.txt.z.out")
+}
+
+#[test]
+fn zero_z_c() -> TestResult {
+    run(&["-z", "-c", ZERO], "tests/expected/zero.txt.zc.out")
+}
+
+#[test]
+fn zero_z_d() -> TestResult {
+    run(&["-z", "-d", ZERO], "tests/expected/zero.txt.zd.out")
+}
+
+#[test]
+fn zero_z_i() -> TestResult {
+    run(&["-z", "-i", ZERO], "tests/expected/zero.txt.zi.out")
+}
@@ -0,0 +1,56 @@
+//! Throughput of wcr against GNU wc, when it is installed, for the common
+//! combinations of counts. Both run in the C.UTF-8 locale so that -m and -L
+//! decode the same characters.
+//!
+//!     cargo bench -p wcr
+//!     WCR_BENCH_MB=10240 cargo bench -p wcr
+
+use common::bench::{installed, megabytes, report, write_input, BenchResult, RUNS};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+const PRG: &str = env!("CARGO_BIN_EXE_wcr");
+
+fn main() -> BenchResult<()> {
+    let megabytes = megabytes("WCR_BENCH_MB");
+    let size = megabytes * 1024 * 1024;
+
+    // Numbered lines, some of them with multibyte characters
+    let input = env::temp_dir().join(format!("wcr-bench-{}.in", std::process::id()));
+    write_input(&input, size, |number| {
+        let text = match number % 8 {
+            0 => "Le cœur a ses raisons que la raison ne connaît point.",
+            _ => "The quick brown fox jumps over the lazy dog.",
+        };
+        format!("{:>10}\t{}\n", number, text)
+    })?;
+
+    println!("counting {} MiB, best of {} runs", megabytes, RUNS);
+    for args in [&["-l"][..], &["-c"], &["-w"], &[], &["-m"], &["-L"]] {
+        for (name, program) in [("wcr", PRG), ("wc", "wc")] {
+            if !installed(program) {
+                continue;
+            }
+            let label = format!("{} {}", name, args.join(" "));
+            report(&label, size, || spawn(program, args, &input))?;
+        }
+    }
+
+    fs::remove_file(&input)?;
+    Ok(())
+}
+
+fn spawn(program: &str, args: &[&str], input: &Path) -> BenchResult<()> {
+    let status = Command::new(program)
+        .args(args)
+        .arg(input)
+        .env("LC_ALL", "C.UTF-8")
+        .stdout(Stdio::null())
+        .status()?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", program, status).into());
+    }
+    Ok(())
+}
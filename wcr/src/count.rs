@@ -0,0 +1,165 @@
+//! Counting over large blocks of input instead of line by line. Newlines
+//! are found with memchr's vectorized search; words, characters and line
+//! widths come from a state machine that carries over from one block to the
+//! next, so a word or a multibyte character split between blocks is still
+//! counted once.
+//!
+//! Input is decoded as UTF-8. Characters are Unicode scalar values, or
+//! extended grapheme clusters with --graphemes; words are separated by
+//! characters with the White_Space property; widths follow East Asian
+//! Width, so CJK and emoji take two columns and combining marks none. Like
+//! GNU wc, bytes that are not valid UTF-8 show up in the byte count only:
+//! they are not characters, take no width and neither start nor end a word.
+
+use crate::{Config, FileInfo};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthChar;
+
+/// Size of the blocks read from each input.
+pub const BUFFER_SIZE: usize = 256 * 1024;
+
+pub struct Counter {
+    info: FileInfo,
+    /// Whether words, characters or widths are wanted, which needs every
+    /// character decoded rather than just the newlines found.
+    decode: bool,
+    in_word: bool,
+    /// Display column reached on the current line.
+    position: usize,
+    /// The start of a character that continues in the next block.
+    partial: Vec<u8>,
+    /// With --graphemes, the last cluster seen, which the next block may
+    /// still add to.
+    cluster: Option<String>,
+}
+
+impl Counter {
+    pub fn new(config: &Config) -> Counter {
+        Counter {
+            info: FileInfo::default(),
+            decode: config.words || config.chars || config.max_line_length,
+            in_word: false,
+            position: 0,
+            partial: Vec::with_capacity(4),
+            cluster: config.graphemes.then(String::new),
+        }
+    }
+
+    /// Counts the next block of input.
+    pub fn feed(&mut self, mut block: &[u8]) {
+        self.info.num_bytes += block.len();
+        self.info.num_lines += memchr::memchr_iter(b'\n', block).count();
+        if !self.decode {
+            return;
+        }
+
+        if !self.partial.is_empty() {
+            let continuation = block
+                .iter()
+                .take(3)
+                .take_while(|&&b| is_continuation(b))
+                .count();
+            let mut pending = std::mem::take(&mut self.partial);
+            pending.extend_from_slice(&block[..continuation]);
+            block = &block[continuation..];
+            self.decode_bytes(&pending, block.is_empty());
+        }
+        self.decode_bytes(block, true);
+    }
+
+    /// Counts whatever the last block left unfinished.
+    pub fn finish(mut self) -> FileInfo {
+        let partial = std::mem::take(&mut self.partial);
+        self.decode_bytes(&partial, false);
+        self.end_cluster();
+        self.info.max_line_length = self.info.max_line_length.max(self.position);
+        self.info
+    }
+
+    /// Decodes `bytes`, skipping invalid sequences. When more input may
+    /// follow, a character cut off at the end is kept for later.
+    fn decode_bytes(&mut self, bytes: &[u8], more: bool) {
+        let end = if more {
+            incomplete_start(bytes)
+        } else {
+            bytes.len()
+        };
+        self.partial.extend_from_slice(&bytes[end..]);
+
+        for chunk in bytes[..end].utf8_chunks() {
+            let valid = chunk.valid();
+            for c in valid.chars() {
+                self.step(c);
+            }
+            match self.cluster.take() {
+                None => self.info.num_chars += valid.chars().count(),
+                Some(mut cluster) => {
+                    // Only the last cluster can go on in the next chunk
+                    cluster.push_str(valid);
+                    let mut clusters = cluster.graphemes(true);
+                    let last = clusters.next_back().unwrap_or_default().to_string();
+                    self.info.num_chars += clusters.count();
+                    self.cluster = Some(last);
+                }
+            }
+            if !chunk.invalid().is_empty() {
+                self.end_cluster();
+            }
+        }
+    }
+
+    /// Counts the pending grapheme cluster, if there is one.
+    fn end_cluster(&mut self) {
+        if let Some(cluster) = &mut self.cluster {
+            if !cluster.is_empty() {
+                self.info.num_chars += 1;
+                cluster.clear();
+            }
+        }
+    }
+
+    /// Follows GNU wc for widths: tabs advance to the next multiple of
+    /// eight, carriage returns and form feeds start over at column zero and
+    /// control characters take no space.
+    #[inline]
+    fn step(&mut self, c: char) {
+        if c.is_whitespace() {
+            self.in_word = false;
+        } else if !self.in_word {
+            self.in_word = true;
+            self.info.num_words += 1;
+        }
+
+        match c {
+            '\n' | '\r' | '\x0c' => {
+                self.info.max_line_length = self.info.max_line_length.max(self.position);
+                self.position = 0;
+            }
+            '\t' => self.position += 8 - self.position % 8,
+            c => self.position += c.width().unwrap_or(0),
+        }
+    }
+}
+
+fn is_continuation(b: u8) -> bool {
+    b & 0xc0 == 0x80
+}
+
+/// Where a multibyte character left incomplete at the end of `bytes`
+/// starts, or the length of `bytes` when the last character is whole.
+fn incomplete_start(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(3) {
+        let i = bytes.len() - back;
+        if is_continuation(bytes[i]) {
+            continue;
+        }
+        let width = match bytes[i] {
+            0xc2..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf4 => 4,
+            _ => 1,
+        };
+        return if width > back { i } else { bytes.len() };
+    }
+    bytes.len()
+}
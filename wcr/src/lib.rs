@@ -0,0 +1,526 @@
+use clap::{Arg, Command};
+use common::{flag, open, Error, MyResult};
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Read};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+mod count;
+mod records;
+
+use count::{Counter, BUFFER_SIZE};
+use records::{format_record, header, Format, Record};
+
+#[derive(Debug)]
+pub struct Config {
+    files: Vec<String>,
+    lines: bool,
+    words: bool,
+    bytes: bool,
+    chars: bool,
+    max_line_length: bool,
+    jobs: usize,
+    files0_from: Option<String>,
+    format: Format,
+    graphemes: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct FileInfo {
+    num_lines: usize,
+    num_words: usize,
+    num_bytes: usize,
+    num_chars: usize,
+    max_line_length: usize,
+}
+
+/// What counting one input came to.
+enum Counted {
+    /// The input could not be opened, so it gets no row.
+    Unopened(Error),
+    /// Reading failed part way; like GNU wc the row shows zeros.
+    Unreadable(Error),
+    /// A name from --files0-from that cannot be used, with the reason.
+    Invalid(String),
+    Done(FileInfo),
+}
+
+pub fn run(config: Config) -> MyResult<()> {
+    // With no file operands stdin is read and, like GNU wc, no name is shown
+    let named = !config.files.is_empty() || config.files0_from.is_some();
+    let (files, width) = match &config.files0_from {
+        Some(list) => {
+            let (files, regular) = read_files0(list)?;
+            // GNU wc only sizes the columns when it can read the whole list
+            // up front; names streamed from a pipe get the narrowest width
+            let width = if regular {
+                number_width(&config, &files)
+            } else {
+                1
+            };
+            (files, width)
+        }
+        None if named => (config.files.clone(), number_width(&config, &config.files)),
+        None => {
+            let files = vec![String::from("-")];
+            let width = number_width(&config, &files);
+            (files, width)
+        }
+    };
+    // stdin and names that are rejected outright are dealt with in turn by
+    // the printing thread, so that only the first "-" sees its contents
+    let in_turn = |name: &str| name == "-" || (name.is_empty() && config.files0_from.is_some());
+
+    if let Some(header) = header(config.format, &config) {
+        println!("{}", header);
+    }
+
+    let mut total = FileInfo::default();
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        // Workers take the files in order and the results are printed in
+        // that same order as they come in
+        for _ in 0..config.jobs.min(files.len()) {
+            let (config, next, files) = (&config, &next, &files);
+            let sender = sender.clone();
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                match files.get(i).map(String::as_str) {
+                    None => break,
+                    Some(filename) if in_turn(filename) => {}
+                    Some(filename) => {
+                        if sender.send((i, count_file(filename, config))).is_err() {
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        let mut pending: Vec<Option<Counted>> = files.iter().map(|_| None).collect();
+        for (i, filename) in files.iter().enumerate() {
+            let counted = if filename.is_empty() && in_turn(filename) {
+                let list = config.files0_from.as_deref().unwrap_or_default();
+                Counted::Invalid(format!("{}:{}: invalid zero-length file name", list, i + 1))
+            } else if filename == "-" && config.files0_from.as_deref() == Some("-") {
+                Counted::Invalid(String::from(
+                    "when reading file names from stdin, no file name of '-' allowed",
+                ))
+            } else if in_turn(filename) {
+                count_file(filename, &config)
+            } else {
+                loop {
+                    if let Some(counted) = pending[i].take() {
+                        break counted;
+                    }
+                    let (j, counted) = receiver.recv().expect("a counting thread panicked");
+                    pending[j] = Some(counted);
+                }
+            };
+
+            let (info, error) = match counted {
+                Counted::Unopened(e) => {
+                    eprintln!("{}: {}", filename, e);
+                    (None, Some(e.to_string()))
+                }
+                Counted::Unreadable(e) => {
+                    eprintln!("{}: {}", filename, e);
+                    (Some(FileInfo::default()), Some(e.to_string()))
+                }
+                Counted::Invalid(reason) => {
+                    eprintln!("{}", reason);
+                    (None, Some(reason))
+                }
+                Counted::Done(info) => (Some(info), None),
+            };
+            let Some(info) =
+                info.or_else(|| (config.format != Format::Text).then(FileInfo::default))
+            else {
+                continue;
+            };
+
+            if config.format == Format::Text {
+                let name = if named { Some(filename.as_str()) } else { None };
+                println!("{}", format_row(&config, &info, width, name));
+            } else {
+                let record = match &error {
+                    Some(message) => Record::Error {
+                        path: filename,
+                        message,
+                    },
+                    None => Record::File {
+                        path: filename,
+                        info: &info,
+                    },
+                };
+                println!("{}", format_record(config.format, &config, &record));
+            }
+
+            total.num_lines += info.num_lines;
+            total.num_words += info.num_words;
+            total.num_bytes += info.num_bytes;
+            total.num_chars += info.num_chars;
+            total.max_line_length = total.max_line_length.max(info.max_line_length);
+        }
+    });
+
+    if config.format != Format::Text {
+        let record = Record::Total(&total);
+        println!("{}", format_record(config.format, &config, &record));
+    } else if files.len() > 1 {
+        println!("{}", format_row(&config, &total, width, Some("total")));
+    }
+    Ok(())
+}
+
+fn count_file(filename: &str, config: &Config) -> Counted {
+    match open(filename) {
+        Err(e) => Counted::Unopened(e),
+        Ok(file) => match count(file, config) {
+            Err(e) => Counted::Unreadable(e),
+            Ok(info) => Counted::Done(info),
+        },
+    }
+}
+
+/// Reads the NUL-terminated names in `list` ("-" for stdin), and whether
+/// `list` is a regular file.
+fn read_files0(list: &str) -> MyResult<(Vec<String>, bool)> {
+    let path = if list == "-" { "/dev/stdin" } else { list };
+    let regular = fs::metadata(path).is_ok_and(|metadata| metadata.is_file());
+
+    let mut names = Vec::new();
+    open(list)
+        .and_then(|mut file| Ok(file.read_to_end(&mut names)?))
+        .map_err(|e| Error::Usage(format!("cannot open '{}' for reading: {}", list, e)))?;
+    if names.last() == Some(&0) {
+        names.pop();
+    }
+    if names.is_empty() {
+        return Ok((vec![], regular));
+    }
+    let files = names
+        .split(|&b| b == 0)
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .collect();
+    Ok((files, regular))
+}
+
+/// Counts `file` in large blocks, decoding characters only when words,
+/// characters or line widths are wanted. See the `count` module for how
+/// Unicode and invalid UTF-8 are treated.
+pub fn count(mut file: impl Read, config: &Config) -> MyResult<FileInfo> {
+    let mut counter = Counter::new(config);
+    let mut buffer = vec![0; BUFFER_SIZE];
+    loop {
+        match file.read(&mut buffer) {
+            Ok(0) => return Ok(counter.finish()),
+            Ok(bytes) => counter.feed(&buffer[..bytes]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Width of every column, computed the way GNU wc does: wide enough for the
+/// combined size of all regular files, at least seven when any input is not
+/// a regular file, and a bare one when a single value is printed.
+fn number_width(config: &Config, files: &[String]) -> usize {
+    let columns = [
+        config.lines,
+        config.words,
+        config.chars,
+        config.bytes,
+        config.max_line_length,
+    ];
+    if files.len() == 1 && columns.iter().filter(|&&c| c).count() == 1 {
+        return 1;
+    }
+
+    let mut minimum_width = 1;
+    let mut regular_total = 0;
+    for (i, filename) in files.iter().enumerate() {
+        let path = match filename.as_str() {
+            "-" => "/dev/stdin",
+            name => name,
+        };
+        match fs::metadata(path) {
+            Ok(metadata) if metadata.is_file() => regular_total += metadata.len(),
+            Ok(_) => minimum_width = 7,
+            // GNU wc falls back to the narrowest width if the first input
+            // cannot be examined
+            Err(_) if i == 0 => return 1,
+            Err(_) => {}
+        }
+    }
+
+    regular_total.to_string().len().max(minimum_width)
+}
+
+fn format_row(config: &Config, info: &FileInfo, width: usize, name: Option<&str>) -> String {
+    let columns = [
+        (config.lines, info.num_lines),
+        (config.words, info.num_words),
+        (config.chars, info.num_chars),
+        (config.bytes, info.num_bytes),
+        (config.max_line_length, info.max_line_length),
+    ];
+
+    let mut row = columns
+        .iter()
+        .filter(|(show, _)| *show)
+        .map(|(_, value)| format!("{:>width$}", value))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if let Some(name) = name {
+        row.push(' ');
+        row.push_str(name);
+    }
+    row
+}
+
+pub fn get_args() -> MyResult<Config> {
+    get_args_from(std::env::args_os())
+}
+
+/// Like `get_args`, but parses `args` (whose first item is the program name)
+/// instead of the process arguments.
+pub fn get_args_from<I, T>(args: I) -> MyResult<Config>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let mut matches = Command::new("wcr")
+        .version("0.1.0")
+        .author("Derek Warner <derekw3@illinois.edu>")
+        .about("A rusty wc")
+        .arg(
+            Arg::new("files")
+                .help("Input file(s)")
+                .value_name("FILE")
+                .num_args(1..),
+        )
+        .arg(
+            Arg::new("lines")
+                .short('l')
+                .long("lines")
+                .help("Show line count")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("words")
+                .short('w')
+                .long("words")
+                .help("Show word count")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("bytes")
+                .short('c')
+                .long("bytes")
+                .help("Show byte count")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("chars")
+                .short('m')
+                .long("chars")
+                .help("Show character count")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("graphemes")
+                .long("graphemes")
+                .help("Count grapheme clusters rather than code points as characters")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("max_line_length")
+                .short('L')
+                .long("max-line-length")
+                .help("Show maximum display width")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("jobs")
+                .short('j')
+                .long("jobs")
+                .value_name("N")
+                .help("Count up to N files at once [default: number of CPUs]")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .help("Print text columns or JSON, CSV or TSV records with every count by default")
+                .value_parser(["text", "json", "csv", "tsv"])
+                .default_value("text"),
+        )
+        .arg(
+            Arg::new("files0_from")
+                .long("files0-from")
+                .value_name("F")
+                .help("Read input file names from F, each terminated by a NUL; - means stdin")
+                .num_args(1),
+        )
+        .get_matches_from(args);
+
+    let files_vec: Vec<String> = matches
+        .remove_many("files")
+        .map(|files| files.collect())
+        .unwrap_or_default();
+
+    let mut lines = flag(&matches, "lines");
+    let mut words = flag(&matches, "words");
+    let mut bytes = flag(&matches, "bytes");
+    let mut chars = flag(&matches, "chars");
+    let mut max_line_length = flag(&matches, "max_line_length");
+
+    let format = match matches.remove_one::<String>("format").as_deref() {
+        Some("json") => Format::Json,
+        Some("csv") => Format::Csv,
+        Some("tsv") => Format::Tsv,
+        _ => Format::Text,
+    };
+
+    if [lines, words, bytes, chars, max_line_length]
+        .iter()
+        .all(|v| !v)
+    {
+        lines = true;
+        words = true;
+        bytes = true;
+        // Records have named fields, so they can carry every count
+        if format != Format::Text {
+            chars = true;
+            max_line_length = true;
+        }
+    }
+
+    let files0_from: Option<String> = matches.remove_one("files0_from");
+    if let (Some(_), Some(extra)) = (&files0_from, files_vec.first()) {
+        return Err(Error::Usage(format!(
+            "extra operand '{}'\nfile operands cannot be combined with --files0-from",
+            extra
+        )));
+    }
+
+    let jobs = match matches.remove_one::<String>("jobs") {
+        Some(val) => val
+            .parse()
+            .ok()
+            .filter(|&jobs| jobs > 0)
+            .ok_or_else(|| Error::Usage(format!("illegal job count -- {}", val)))?,
+        None => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+    };
+
+    Ok(Config {
+        files: files_vec,
+        lines,
+        words,
+        bytes,
+        chars,
+        max_line_length,
+        jobs,
+        files0_from,
+        format,
+        graphemes: flag(&matches, "graphemes"),
+    })
+}
+
+#[cfg(test)]
+fn test_config(graphemes: bool) -> Config {
+    Config {
+        files: vec![],
+        lines: true,
+        words: true,
+        bytes: true,
+        chars: true,
+        max_line_length: true,
+        jobs: 1,
+        files0_from: None,
+        format: Format::Text,
+        graphemes,
+    }
+}
+
+#[cfg(test)]
+fn count_all(text: &[u8]) -> FileInfo {
+    count(text, &test_config(false)).unwrap()
+}
+
+#[test]
+fn test_count() {
+    let text = "I don't want the world. I just want your half.\r\n";
+    let expected = FileInfo {
+        num_lines: 1,
+        num_words: 10,
+        num_bytes: 48,
+        num_chars: 48,
+        max_line_length: 46,
+    };
+    assert_eq!(count_all(text.as_bytes()), expected);
+}
+
+#[test]
+fn test_max_line_length() {
+    assert_eq!(count_all(b"").max_line_length, 0);
+    assert_eq!(count_all(b"abc\n").max_line_length, 3);
+    assert_eq!(count_all(b"a\tb").max_line_length, 9);
+    assert_eq!(count_all(b"long line\rshort").max_line_length, 9);
+    assert_eq!(
+        count_all("\u{65e5}\u{672c}\u{8a9e}".as_bytes()).max_line_length,
+        6
+    );
+    assert_eq!(count_all("e\u{301}\u{200b}".as_bytes()).max_line_length, 1);
+}
+
+#[test]
+fn test_unicode() {
+    // No-break and ideographic spaces separate words, zero width ones do not
+    assert_eq!(
+        count_all("a\u{a0}b\u{3000}c\u{200b}d".as_bytes()).num_words,
+        3
+    );
+
+    // Invalid bytes are neither characters nor word separators
+    let info = count_all(b"\xe9\na\xe9b \xff c\xf0\x9f");
+    assert_eq!((info.num_words, info.num_chars, info.num_bytes), (2, 6, 11));
+
+    let family = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467} e\u{301}\r\n";
+    let info = count_all(family.as_bytes());
+    assert_eq!((info.num_chars, info.max_line_length), (10, 8));
+    let info = count(family.as_bytes(), &test_config(true)).unwrap();
+    assert_eq!(info.num_chars, 4);
+}
+
+#[test]
+fn test_count_across_blocks() {
+    // Words, characters and invalid sequences cut at every possible place
+    let text = "caf\u{e9} na\u{ef}ve \u{1f980}\u{3000}x\n\u{1f1eb}\u{1f1f7}\u{1f1e9}e\u{301}\r\n";
+    let mut text = text.as_bytes().to_vec();
+    text.extend(b"\xe2\x82 \xf0\x9f\x80y\xe9\n");
+    for graphemes in [false, true] {
+        let config = test_config(graphemes);
+        let whole = count(&text[..], &config).unwrap();
+        let chars = if graphemes { 22 } else { 25 };
+        assert_eq!((whole.num_words, whole.num_chars), (6, chars));
+        for split in 0..=text.len() {
+            let mut counter = Counter::new(&config);
+            counter.feed(&text[..split]);
+            counter.feed(&text[split..]);
+            assert_eq!(counter.finish(), whole, "split at {}", split);
+        }
+        for size in 1..4 {
+            let mut counter = Counter::new(&config);
+            text.chunks(size).for_each(|block| counter.feed(block));
+            assert_eq!(counter.finish(), whole, "blocks of {}", size);
+        }
+    }
+}
@@ -1,3 +1,6 @@
 fn main() {
-    println!("Hello, world!");
+    if let Err(e) = wcr::get_args().and_then(wcr::run) {
+        eprintln!("{}", e);
+        std::process::exit(e.exit_code());
+    }
 }
@@ -0,0 +1,142 @@
+//! Machine-readable output for `--format`: a record for each input, one
+//! for the total and one for each input that could not be counted. JSON
+//! output has one object per line; CSV and TSV start with a header.
+
+use crate::{Config, FileInfo};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// GNU wc's aligned columns.
+    Text,
+    Json,
+    Csv,
+    Tsv,
+}
+
+pub enum Record<'a> {
+    File { path: &'a str, info: &'a FileInfo },
+    Total(&'a FileInfo),
+    Error { path: &'a str, message: &'a str },
+}
+
+/// The counts `config` asks for, by field name.
+fn counts<'a>(config: &Config, info: &'a FileInfo) -> Vec<(&'static str, &'a usize)> {
+    [
+        (config.lines, "lines", &info.num_lines),
+        (config.words, "words", &info.num_words),
+        (config.chars, "chars", &info.num_chars),
+        (config.bytes, "bytes", &info.num_bytes),
+        (
+            config.max_line_length,
+            "max_line_length",
+            &info.max_line_length,
+        ),
+    ]
+    .into_iter()
+    .filter(|(show, _, _)| *show)
+    .map(|(_, name, value)| (name, value))
+    .collect()
+}
+
+/// The header line for CSV and TSV.
+pub fn header(format: Format, config: &Config) -> Option<String> {
+    let mut fields = vec!["type", "path"];
+    fields.extend(
+        counts(config, &FileInfo::default())
+            .iter()
+            .map(|(name, _)| *name),
+    );
+    fields.push("error");
+    match format {
+        Format::Csv => Some(fields.join(",")),
+        Format::Tsv => Some(fields.join("\t")),
+        Format::Text | Format::Json => None,
+    }
+}
+
+/// Formats `record` as one line of JSON, CSV or TSV.
+pub fn format_record(format: Format, config: &Config, record: &Record) -> String {
+    let empty = FileInfo::default();
+    let (kind, path, info, message) = match record {
+        Record::File { path, info } => ("file", Some(*path), Some(*info), None),
+        Record::Total(info) => ("total", None, Some(*info), None),
+        Record::Error { path, message } => ("error", Some(*path), None, Some(*message)),
+    };
+    let counts = counts(config, info.unwrap_or(&empty));
+
+    if format == Format::Json {
+        let mut fields = vec![format!("\"type\":\"{}\"", kind)];
+        if let Some(path) = path {
+            fields.push(format!("\"path\":{}", json_string(path)));
+        }
+        if info.is_some() {
+            fields.extend(
+                counts
+                    .iter()
+                    .map(|(name, value)| format!("\"{}\":{}", name, value)),
+            );
+        }
+        if let Some(message) = message {
+            fields.push(format!("\"error\":{}", json_string(message)));
+        }
+        return format!("{{{}}}", fields.join(","));
+    }
+
+    let (separator, quote): (&str, fn(&str) -> String) = match format {
+        Format::Tsv => ("\t", tsv_field),
+        _ => (",", csv_field),
+    };
+    let mut fields = vec![kind.to_string(), quote(path.unwrap_or_default())];
+    fields.extend(counts.iter().map(|(_, value)| match info {
+        Some(_) => value.to_string(),
+        None => String::new(),
+    }));
+    fields.push(quote(message.unwrap_or_default()));
+    fields.join(separator)
+}
+
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Quotes a CSV field as RFC 4180 has it, when it needs quoting at all.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// TSV has no quoting, so tabs, newlines and backslashes are escaped.
+fn tsv_field(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+#[test]
+fn test_quoting() {
+    assert_eq!(
+        json_string("a \"b\"\\\n\x01"),
+        "\"a \\\"b\\\"\\\\\\n\\u0001\""
+    );
+    assert_eq!(csv_field("plain name"), "plain name");
+    assert_eq!(csv_field("a,b"), "\"a,b\"");
+    assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    assert_eq!(tsv_field("a\tb\\c\n"), "a\\tb\\\\c\\n");
+}
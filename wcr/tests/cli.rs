@@ -0,0 +1,551 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use rand::{distributions::Alphanumeric, Rng};
+use std::error::Error;
+use std::fs;
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+const PRG: &str = "wcr";
+const EMPTY: &str = "tests/inputs/empty.txt";
+const FOX: &str = "tests/inputs/fox.txt";
+const TYGER: &str = "tests/inputs/tyger.txt";
+const CAFE: &str = "tests/inputs/cafe.txt";
+const UNICODE: &str = "tests/inputs/unicode.txt";
+
+// --------------------------------------------------
+#[test]
+fn usage() -> TestResult {
+    for flag in &["-h", "--help"] {
+        Command::cargo_bin(PRG)?
+            .arg(flag)
+            .assert()
+            .stdout(predicate::str::contains("Usage"));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(7)
+            .map(char::from)
+            .collect();
+
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file() -> TestResult {
+    let bad = gen_bad_file();
+    let expected = format!("{}: .* [(]os error 2[)]", bad);
+    Command::cargo_bin(PRG)?
+        .args([FOX, &bad, EMPTY])
+        .assert()
+        .success()
+        .stderr(predicate::str::is_match(expected)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn directory_counts_as_zero() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .arg("tests")
+        .assert()
+        .success()
+        .stdout("      0       0       0 tests\n")
+        .stderr(predicate::str::contains("tests: "));
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> TestResult {
+    let expected = fs::read_to_string(expected_file)?;
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run_stdin(input_file: &str, args: &[&str], expected_file: &str) -> TestResult {
+    let input = fs::read_to_string(input_file)?;
+    let expected = fs::read_to_string(expected_file)?;
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn empty() -> TestResult {
+    run(&[EMPTY], "tests/expected/empty.txt.out")
+}
+
+#[test]
+fn empty_l() -> TestResult {
+    run(&["-l", EMPTY], "tests/expected/empty.txt.l.out")
+}
+
+#[test]
+fn empty_w() -> TestResult {
+    run(&["-w", EMPTY], "tests/expected/empty.txt.w.out")
+}
+
+#[test]
+fn empty_c() -> TestResult {
+    run(&["-c", EMPTY], "tests/expected/empty.txt.c.out")
+}
+
+#[test]
+fn empty_m() -> TestResult {
+    run(&["-m", EMPTY], "tests/expected/empty.txt.m.out")
+}
+
+#[test]
+fn empty_max() -> TestResult {
+    run(&["-L", EMPTY], "tests/expected/empty.txt.L.out")
+}
+
+#[test]
+fn empty_lwm() -> TestResult {
+    run(
+        &["-l", "-w", "-m", EMPTY],
+        "tests/expected/empty.txt.lwm.out",
+    )
+}
+
+#[test]
+fn empty_cmax() -> TestResult {
+    run(&["-cL", EMPTY], "tests/expected/empty.txt.cL.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn fox() -> TestResult {
+    run(&[FOX], "tests/expected/fox.txt.out")
+}
+
+#[test]
+fn fox_l() -> TestResult {
+    run(&["-l", FOX], "tests/expected/fox.txt.l.out")
+}
+
+#[test]
+fn fox_w() -> TestResult {
+    run(&["-w", FOX], "tests/expected/fox.txt.w.out")
+}
+
+#[test]
+fn fox_c() -> TestResult {
+    run(&["-c", FOX], "tests/expected/fox.txt.c.out")
+}
+
+#[test]
+fn fox_m() -> TestResult {
+    run(&["-m", FOX], "tests/expected/fox.txt.m.out")
+}
+
+#[test]
+fn fox_max() -> TestResult {
+    run(&["-L", FOX], "tests/expected/fox.txt.L.out")
+}
+
+#[test]
+fn fox_lwm() -> TestResult {
+    run(&["-l", "-w", "-m", FOX], "tests/expected/fox.txt.lwm.out")
+}
+
+#[test]
+fn fox_cmax() -> TestResult {
+    run(&["-cL", FOX], "tests/expected/fox.txt.cL.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn tyger() -> TestResult {
+    run(&[TYGER], "tests/expected/tyger.txt.out")
+}
+
+#[test]
+fn tyger_l() -> TestResult {
+    run(&["--lines", TYGER], "tests/expected/tyger.txt.l.out")
+}
+
+#[test]
+fn tyger_w() -> TestResult {
+    run(&["--words", TYGER], "tests/expected/tyger.txt.w.out")
+}
+
+#[test]
+fn tyger_c() -> TestResult {
+    run(&["--bytes", TYGER], "tests/expected/tyger.txt.c.out")
+}
+
+#[test]
+fn tyger_m() -> TestResult {
+    run(&["--chars", TYGER], "tests/expected/tyger.txt.m.out")
+}
+
+#[test]
+fn tyger_max() -> TestResult {
+    run(
+        &["--max-line-length", TYGER],
+        "tests/expected/tyger.txt.L.out",
+    )
+}
+
+#[test]
+fn tyger_lwm() -> TestResult {
+    run(
+        &["-l", "-w", "-m", TYGER],
+        "tests/expected/tyger.txt.lwm.out",
+    )
+}
+
+#[test]
+fn tyger_cmax() -> TestResult {
+    run(&["-cL", TYGER], "tests/expected/tyger.txt.cL.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn cafe() -> TestResult {
+    run(&[CAFE], "tests/expected/cafe.txt.out")
+}
+
+#[test]
+fn cafe_l() -> TestResult {
+    run(&["-l", CAFE], "tests/expected/cafe.txt.l.out")
+}
+
+#[test]
+fn cafe_w() -> TestResult {
+    run(&["-w", CAFE], "tests/expected/cafe.txt.w.out")
+}
+
+#[test]
+fn cafe_c() -> TestResult {
+    run(&["-c", CAFE], "tests/expected/cafe.txt.c.out")
+}
+
+#[test]
+fn cafe_m() -> TestResult {
+    run(&["-m", CAFE], "tests/expected/cafe.txt.m.out")
+}
+
+#[test]
+fn cafe_max() -> TestResult {
+    run(&["-L", CAFE], "tests/expected/cafe.txt.L.out")
+}
+
+#[test]
+fn cafe_lwm() -> TestResult {
+    run(&["-l", "-w", "-m", CAFE], "tests/expected/cafe.txt.lwm.out")
+}
+
+#[test]
+fn cafe_cmax() -> TestResult {
+    run(&["-cL", CAFE], "tests/expected/cafe.txt.cL.out")
+}
+
+#[test]
+fn unicode() -> TestResult {
+    run(&[UNICODE], "tests/expected/unicode.txt.out")
+}
+
+#[test]
+fn unicode_l() -> TestResult {
+    run(&["-l", UNICODE], "tests/expected/unicode.txt.l.out")
+}
+
+#[test]
+fn unicode_w() -> TestResult {
+    run(&["-w", UNICODE], "tests/expected/unicode.txt.w.out")
+}
+
+#[test]
+fn unicode_c() -> TestResult {
+    run(&["-c", UNICODE], "tests/expected/unicode.txt.c.out")
+}
+
+#[test]
+fn unicode_m() -> TestResult {
+    run(&["-m", UNICODE], "tests/expected/unicode.txt.m.out")
+}
+
+#[test]
+fn unicode_max() -> TestResult {
+    run(&["-L", UNICODE], "tests/expected/unicode.txt.L.out")
+}
+
+#[test]
+fn unicode_lwm() -> TestResult {
+    run(&["-lwm", UNICODE], "tests/expected/unicode.txt.lwm.out")
+}
+
+#[test]
+fn unicode_graphemes() -> TestResult {
+    run(
+        &["-m", "--graphemes", UNICODE],
+        "tests/expected/unicode.txt.graphemes.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn tyger_stdin() -> TestResult {
+    run_stdin(TYGER, &[], "tests/expected/tyger.txt.stdin.out")
+}
+
+#[test]
+fn tyger_l_stdin() -> TestResult {
+    run_stdin(TYGER, &["-l"], "tests/expected/tyger.txt.l.stdin.out")
+}
+
+#[test]
+fn tyger_dash_stdin() -> TestResult {
+    run_stdin(
+        TYGER,
+        &["-", FOX],
+        "tests/expected/tyger.txt.dash.stdin.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn all() -> TestResult {
+    run(&[EMPTY, FOX, TYGER, CAFE], "tests/expected/all.out")
+}
+
+#[test]
+fn all_l() -> TestResult {
+    run(&[EMPTY, FOX, TYGER, CAFE, "-l"], "tests/expected/all.l.out")
+}
+
+#[test]
+fn all_w() -> TestResult {
+    run(&[EMPTY, FOX, TYGER, CAFE, "-w"], "tests/expected/all.w.out")
+}
+
+#[test]
+fn all_c() -> TestResult {
+    run(&[EMPTY, FOX, TYGER, CAFE, "-c"], "tests/expected/all.c.out")
+}
+
+#[test]
+fn all_m() -> TestResult {
+    run(&[EMPTY, FOX, TYGER, CAFE, "-m"], "tests/expected/all.m.out")
+}
+
+#[test]
+fn all_max() -> TestResult {
+    run(&[EMPTY, FOX, TYGER, CAFE, "-L"], "tests/expected/all.L.out")
+}
+
+#[test]
+fn all_lwmcl() -> TestResult {
+    run(
+        &["-lwmcL", EMPTY, FOX, TYGER, CAFE],
+        "tests/expected/all.lwmcL.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn all_one_job() -> TestResult {
+    run(
+        &["--jobs", "1", EMPTY, FOX, TYGER, CAFE],
+        "tests/expected/all.out",
+    )
+}
+
+#[test]
+fn all_three_jobs() -> TestResult {
+    run(&["-j3", EMPTY, FOX, TYGER, CAFE], "tests/expected/all.out")
+}
+
+#[test]
+fn many_files_in_order() -> TestResult {
+    let files = [EMPTY, FOX, TYGER, CAFE].repeat(50);
+    let mut sequential = files.to_vec();
+    sequential.push("--jobs=1");
+    let expected = Command::cargo_bin(PRG)?.args(&sequential).output()?.stdout;
+    Command::cargo_bin(PRG)?
+        .args(&files)
+        .args(["--jobs", "8"])
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+#[test]
+fn stdin_between_files() -> TestResult {
+    run_stdin(
+        TYGER,
+        &["-j4", FOX, "-", CAFE, "-"],
+        "tests/expected/tyger.txt.between.stdin.out",
+    )
+}
+
+#[test]
+fn dies_bad_jobs() -> TestResult {
+    for bad in ["0", "x"] {
+        Command::cargo_bin(PRG)?
+            .args(["--jobs", bad, FOX])
+            .assert()
+            .failure()
+            .stderr(format!("illegal job count -- {}\n", bad));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn files0_from() -> TestResult {
+    run(
+        &["--files0-from", "tests/inputs/files0.txt"],
+        "tests/expected/files0.txt.out",
+    )
+}
+
+#[test]
+fn files0_from_lines_max() -> TestResult {
+    run(
+        &["-lL", "--files0-from=tests/inputs/files0.txt"],
+        "tests/expected/files0.txt.lL.out",
+    )
+}
+
+#[test]
+fn files0_from_stdin() -> TestResult {
+    run_stdin(
+        "tests/inputs/files0.txt",
+        &["--files0-from=-"],
+        "tests/expected/files0.txt.stdin.out",
+    )
+}
+
+#[test]
+fn files0_from_stdin_bad_names() -> TestResult {
+    let input = fs::read_to_string("tests/inputs/files0.bad.txt")?;
+    let expected = fs::read_to_string("tests/expected/files0.bad.txt.stdin.out")?;
+    Command::cargo_bin(PRG)?
+        .arg("--files0-from=-")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected)
+        .stderr(
+            "-:2: invalid zero-length file name\n\
+             when reading file names from stdin, no file name of '-' allowed\n",
+        );
+    Ok(())
+}
+
+#[test]
+fn files0_from_empty_list() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .arg("--files0-from=-")
+        .assert()
+        .success()
+        .stdout("");
+    Ok(())
+}
+
+#[test]
+fn dies_files0_from_with_operand() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--files0-from=tests/inputs/files0.txt", FOX])
+        .assert()
+        .failure()
+        .stderr(format!(
+            "extra operand '{}'\nfile operands cannot be combined with --files0-from\n",
+            FOX
+        ));
+    Ok(())
+}
+
+#[test]
+fn dies_files0_from_missing_list() -> TestResult {
+    let bad = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args(["--files0-from", &bad])
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with(format!(
+            "cannot open '{}' for reading: ",
+            bad
+        )));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn format_json() -> TestResult {
+    run(
+        &["--format", "json", EMPTY, FOX, "tests", CAFE],
+        "tests/expected/all.json.out",
+    )
+}
+
+#[test]
+fn format_csv() -> TestResult {
+    run(
+        &["--format", "csv", EMPTY, FOX, "tests", CAFE],
+        "tests/expected/all.csv.out",
+    )
+}
+
+#[test]
+fn format_tsv() -> TestResult {
+    run(
+        &["--format=tsv", EMPTY, FOX, "tests", CAFE],
+        "tests/expected/all.tsv.out",
+    )
+}
+
+#[test]
+fn format_csv_selected_counts() -> TestResult {
+    run(
+        &["--format=csv", "-lw", FOX],
+        "tests/expected/fox.txt.lw.csv.out",
+    )
+}
+
+#[test]
+fn format_json_stdin() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--format=json", "-c"])
+        .write_stdin("hello\n")
+        .assert()
+        .success()
+        .stdout(
+            "{\"type\":\"file\",\"path\":\"-\",\"bytes\":6}\n{\"type\":\"total\",\"bytes\":6}\n",
+        );
+    Ok(())
+}
+
+#[test]
+fn dies_bad_format() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--format=xml", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value 'xml'"));
+    Ok(())
+}